@@ -6,26 +6,444 @@ pub use self::my_psp22_mintable::{Psp22Contract, Psp22ContractRef}; // add
 #[openbrush::contract]
 pub mod my_psp22_mintable {
     use openbrush::{
-        contracts::psp22::extensions::mintable::*,
-        traits::Storage,
+        contracts::{
+            access_control::*,
+            pausable::*,
+            psp22::extensions::{
+                burnable::*,
+                metadata::*,
+                mintable::*,
+            },
+        },
+        modifiers,
+        storage::Mapping,
+        traits::{
+            Storage,
+            String,
+        },
     };
     use ink::prelude::vec::Vec;
 
+    use ink::env::hash;
+
+    use ink::storage::{
+        traits::ManualKey,
+        Lazy,
+    };
+
+    // accounts allowed to mint new tokens (the game contract, the treasury multisig)
+    pub const MINTER: RoleType = ink::selector_id!("MINTER");
+
+    // accounts allowed to stop token movement in an emergency
+    pub const PAUSER: RoleType = ink::selector_id!("PAUSER");
+
+    // mixed into every permit hash so a signature can't be replayed for anything else
+    pub const PERMIT_DOMAIN: &[u8] = b"my_psp22_mintable::permit";
+
+    // bump this and add an arm to `migrate_from` whenever stored data changes shape; releases
+    // before version 1 had no `set_code`, so every upgradeable contract starts at version 1
+    pub const STORAGE_VERSION: u32 = 1;
+
+    // recipients of a single batch transfer
+    pub const MAX_BATCH_SIZE: usize = 200;
+
+    // highest transfer fee the admin can set, in basis points (10%)
+    pub const MAX_FEE_RATE: u16 = 1000;
+
+    // spending limits of a game contract allowed to call `transfer_from_contract`
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct TrustedSpender {
+        // maximum amount moved by a single call
+        pub max_per_call: Balance,
+        // maximum amount moved within one period
+        pub max_per_period: Balance,
+        // period length in milliseconds
+        pub period: u64,
+    }
+
+    // how much a trusted spender has moved in its current period
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SpendingWindow {
+        pub start: u64,
+        pub spent: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        spender: AccountId,
+        value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct TransferFee {
+        #[ink(topic)]
+        from: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        // amount sent before the fee
+        value: Balance,
+        fee: Balance,
+        treasury: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AccountFrozen {
+        #[ink(topic)]
+        account: AccountId,
+        reason: u8,
+    }
+
+    #[ink(event)]
+    pub struct AccountUnfrozen {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        account: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DelegateChanged {
+        #[ink(topic)]
+        delegator: AccountId,
+        #[ink(topic)]
+        from_delegate: Option<AccountId>,
+        #[ink(topic)]
+        to_delegate: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct DelegateVotesChanged {
+        #[ink(topic)]
+        delegate: AccountId,
+        previous_votes: Balance,
+        new_votes: Balance,
+    }
+
+    // what a checkpoint history is tracking
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum CheckpointKey {
+        Balance(AccountId),
+        Votes(AccountId),
+        TotalSupply,
+    }
+
+    // a value as of some block
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Checkpoint {
+        pub block: u32,
+        // block timestamp in milliseconds
+        pub timestamp: u64,
+        pub value: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AirdropClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        round: u32,
+        amount: Balance,
+    }
+
+    // a running Merkle airdrop, funded with tokens held by this contract
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Airdrop {
+        // root of a tree with keccak256(scale(account, amount)) leaves and sorted pair hashing
+        pub merkle_root: [u8; 32],
+        // tokens not claimed yet
+        pub remaining: Balance,
+        // claims are closed after this time, in milliseconds
+        pub expiry: u64,
+        // receives the unclaimed tokens after expiry
+        pub treasury: AccountId,
+    }
+
+    // tokens that become mintable over time
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EmissionSchedule {
+        // time the first period starts, in milliseconds
+        pub start: u64,
+        // period length in milliseconds
+        pub period: u64,
+        // tokens unlocked at the end of every period
+        pub amount_per_period: Balance,
+        // tokens minted under the schedule so far
+        pub minted: Balance,
+    }
+
     #[ink(storage)]
     #[derive(Default, Storage)]
     pub struct Psp22Contract {
+        // Storage version 1 is the first release with `set_code`, so its root cell is the layout
+        // every upgrade starts from. Plain fields share that cell, so fields added after version 1
+        // must be mappings, `Lazy` cells or openbrush data under its own key; otherwise the root
+        // cell no longer decodes after `set_code` and even `migrate` can't run.
+
         #[storage_field]
         psp22: psp22::Data,
+
+        #[storage_field]
+        access: access_control::Data,
+
+        #[storage_field]
+        metadata: metadata::Data,
+
+        #[storage_field]
+        pause: pausable::Data,
+
+        // game contracts allowed to move tokens of opted-in accounts
+        pub trusted_spenders: Mapping<AccountId, TrustedSpender>,
+
+        // spending of each trusted spender in the current period
+        pub spending_windows: Mapping<AccountId, SpendingWindow>,
+
+        // (owner, spender) pairs where the owner allowed the trusted spender to move its tokens
+        pub spender_opt_ins: Mapping<(AccountId, AccountId), bool>,
+
+        // tokens removed from the supply so far
+        pub total_burned: Lazy<Balance, ManualKey<{ ink::selector_id!("total_burned") }>>,
+
+        // total supply can never go above this
+        pub max_supply: Lazy<Balance, ManualKey<{ ink::selector_id!("max_supply") }>>,
+
+        // once set, every mint has to come out of the unlocked emission
+        pub emission: Lazy<Option<EmissionSchedule>, ManualKey<{ ink::selector_id!("emission") }>>,
+
+        // next permit nonce of each owner
+        pub permit_nonces: Mapping<AccountId, u64>,
+
+        // account each holder lends its voting power to
+        pub delegates: Mapping<AccountId, AccountId>,
+
+        // accounts that can neither send nor receive, with the reason code they were frozen for
+        pub frozen_accounts: Mapping<AccountId, u8>,
+
+        // fee taken from every transfer, in basis points (10000 = 100%)
+        pub fee_rate: Lazy<u16, ManualKey<{ ink::selector_id!("fee_rate") }>>,
+
+        // receives the transfer fees
+        pub fee_treasury: Lazy<Option<AccountId>, ManualKey<{ ink::selector_id!("fee_treasury") }>>,
+
+        // accounts that neither pay nor cause fees (game contracts, exchanges)
+        pub fee_exempt: Mapping<AccountId, bool>,
+
+        // the current airdrop, if there is one
+        pub airdrop: Lazy<Option<Airdrop>, ManualKey<{ ink::selector_id!("airdrop") }>>,
+
+        // number of airdrops started so far
+        pub airdrop_round: Lazy<u32, ManualKey<{ ink::selector_id!("airdrop_round") }>>,
+
+        // (round, account) pairs that already claimed
+        pub airdrop_claims: Mapping<(u32, AccountId), bool>,
+
+        // checkpoint histories, ordered by block
+        pub checkpoints: Mapping<(CheckpointKey, u32), Checkpoint>,
+
+        // number of checkpoints in each history
+        pub checkpoint_counts: Mapping<CheckpointKey, u32>,
+
+        // version of the layout the stored data is in; unset in the first release
+        pub storage_version: Lazy<u32, ManualKey<{ ink::selector_id!("storage_version") }>>,
+    }
+
+    impl PSP22 for Psp22Contract {
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error> {
+            let from = Self::env().caller();
+            self.transfer_with_fee(from, to, value, data)
+        }
+
+        #[ink(message)]
+        fn transfer_from(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error> {
+            let caller = Self::env().caller();
+            let allowance = self.allowance(from, caller);
+            if allowance < value {
+                return Err(PSP22Error::InsufficientAllowance)
+            }
+            self._approve_from_to(from, caller, allowance - value)?;
+            self.transfer_with_fee(from, to, value, data)
+        }
+    }
+
+    impl psp22::Internal for Psp22Contract {
+        fn _emit_transfer_event(&self, from: Option<AccountId>, to: Option<AccountId>, amount: Balance) {
+            self.env().emit_event(Transfer { from, to, value: amount });
+        }
+
+        fn _emit_approval_event(&self, owner: AccountId, spender: AccountId, amount: Balance) {
+            self.env().emit_event(Approval { owner, spender, value: amount });
+        }
+    }
+
+    impl PSP22Metadata for Psp22Contract {}
+
+    impl PSP22Burnable for Psp22Contract {
+        /// Burns `amount` of `account`'s tokens.
+        ///
+        /// Anyone can burn their own tokens; burning someone else's spends the caller's allowance.
+        #[ink(message)]
+        fn burn(&mut self, account: AccountId, amount: Balance) -> Result<(), PSP22Error> {
+            let caller = Self::env().caller();
+            if caller != account {
+                let allowance = self.allowance(account, caller);
+                if allowance < amount {
+                    return Err(PSP22Error::InsufficientAllowance)
+                }
+                self._approve_from_to(account, caller, allowance - amount)?;
+            }
+            self._burn_from(account, amount)
+        }
+    }
+
+    impl psp22::Transfer for Psp22Contract {
+        fn _before_token_transfer(
+            &mut self,
+            from: Option<&AccountId>,
+            to: Option<&AccountId>,
+            amount: &Balance,
+        ) -> Result<(), PSP22Error> {
+            if self.paused() {
+                return Err(PausableError::Paused.into())
+            }
+            if from.map_or(false, |account| self.is_frozen(*account)) {
+                return Err(PSP22Error::Custom(String::from("SenderFrozen")))
+            }
+            if to.map_or(false, |account| self.is_frozen(*account)) {
+                return Err(PSP22Error::Custom(String::from("RecipientFrozen")))
+            }
+            // minting
+            if from.is_none() {
+                let new_supply = self.total_supply().checked_add(*amount);
+                if new_supply.map_or(true, |supply| supply > self.get_max_supply()) {
+                    return Err(PSP22Error::Custom(String::from("CapExceeded")))
+                }
+            }
+            Ok(())
+        }
+
+        fn _after_token_transfer(
+            &mut self,
+            from: Option<&AccountId>,
+            to: Option<&AccountId>,
+            amount: &Balance,
+        ) -> Result<(), PSP22Error> {
+            // burning
+            if to.is_none() {
+                let total_burned = self.get_total_burned().saturating_add(*amount);
+                self.total_burned.set(&total_burned);
+            }
+
+            // minting or burning
+            if from.is_none() || to.is_none() {
+                let total_supply = self.total_supply();
+                self.write_checkpoint(CheckpointKey::TotalSupply, total_supply);
+            }
+            for account in [from, to].into_iter().flatten() {
+                let balance = self.balance_of(*account);
+                self.write_checkpoint(CheckpointKey::Balance(*account), balance);
+            }
+
+            let from_delegate = from.and_then(|account| self.delegates.get(account));
+            let to_delegate = to.and_then(|account| self.delegates.get(account));
+            self.move_voting_power(from_delegate, to_delegate, *amount);
+            Ok(())
+        }
+    }
+
+    impl AccessControl for Psp22Contract {}
+
+    impl Pausable for Psp22Contract {}
+
+    impl pausable::Internal for Psp22Contract {
+        fn _emit_paused_event(&self, account: AccountId) {
+            self.env().emit_event(Paused { account });
+        }
+
+        fn _emit_unpaused_event(&self, account: AccountId) {
+            self.env().emit_event(Unpaused { account });
+        }
     }
 
-    impl PSP22 for Psp22Contract {}
-    impl PSP22Mintable for Psp22Contract {}
+    impl PSP22Mintable for Psp22Contract {
+        #[ink(message)]
+        #[modifiers(only_role(MINTER))]
+        fn mint(&mut self, account: AccountId, amount: Balance) -> Result<(), PSP22Error> {
+            self.use_emission(amount)?;
+            self._mint_to(account, amount)
+        }
+    }
 
     impl Psp22Contract {
+        /// The constructor
+        ///
+        /// `admin` gets the admin, minter and pauser roles and can hand them out to other accounts.
+        /// `total_supply` is minted to the caller and has to fit under `max_supply`.
         #[ink(constructor)]
-        pub fn new(total_supply: Balance) -> Self {
+        pub fn new(
+            total_supply: Balance,
+            max_supply: Balance,
+            admin: AccountId,
+            name: Option<String>,
+            symbol: Option<String>,
+            decimal: u8
+        ) -> Self {
             let mut instance = Self::default();
 
+            instance.metadata.name = name;
+            instance.metadata.symbol = symbol;
+            instance.metadata.decimals = decimal;
+            instance.max_supply.set(&max_supply);
+            instance.storage_version.set(&STORAGE_VERSION);
+
+            instance._init_with_admin(admin);
+            instance._setup_role(MINTER, admin);
+            instance._setup_role(PAUSER, admin);
+
             assert!(instance._mint_to(Self::env().caller(), total_supply).is_ok());
 
             instance
@@ -36,10 +454,1813 @@ pub mod my_psp22_mintable {
             self.mint(account, amount)
         }
 
+        #[ink(message)]
+        pub fn get_max_supply(&self) -> Balance {
+            self.max_supply.get().unwrap_or_default()
+        }
+
+        /// Puts minting on a schedule: `amount_per_period` unlocks every `period` milliseconds from `start`.
+        ///
+        /// The schedule can only be set once so the published tokenomics can't be changed later.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_emission_schedule(&mut self, start: u64, period: u64, amount_per_period: Balance) -> Result<(), PSP22Error> {
+            if self.get_emission_schedule().is_some() {
+                return Err(PSP22Error::Custom(String::from("EmissionAlreadySet")))
+            }
+            if period == 0 || amount_per_period == 0 {
+                return Err(PSP22Error::Custom(String::from("InvalidEmissionSchedule")))
+            }
+            self.emission.set(&Some(EmissionSchedule { start, period, amount_per_period, minted: 0 }));
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_emission_schedule(&self) -> Option<EmissionSchedule> {
+            self.emission.get().flatten()
+        }
+
+        /// Returns how much can be minted right now without breaking the schedule or the cap.
+        #[ink(message)]
+        pub fn get_unlocked_emission(&self) -> Balance {
+            let headroom = self.get_max_supply().saturating_sub(self.total_supply());
+            match self.get_emission_schedule() {
+                Some(emission) => {
+                    let current_time = Self::env().block_timestamp();
+                    let periods = current_time.saturating_sub(emission.start) / emission.period;
+                    let unlocked = emission.amount_per_period.saturating_mul(periods as Balance);
+                    unlocked.saturating_sub(emission.minted).min(headroom)
+                }
+                None => headroom,
+            }
+        }
+
+        /// Mints everything the schedule has unlocked so far to `to`.
+        #[ink(message)]
+        #[modifiers(only_role(MINTER))]
+        pub fn release_emission(&mut self, to: AccountId) -> Result<Balance, PSP22Error> {
+            if self.get_emission_schedule().is_none() {
+                return Err(PSP22Error::Custom(String::from("NoEmissionSchedule")))
+            }
+            let amount = self.get_unlocked_emission();
+            if amount > 0 {
+                self.use_emission(amount)?;
+                self._mint_to(to, amount)?;
+            }
+            Ok(amount)
+        }
+
+        /// Moves `value` from `from` to `to` on behalf of a trusted game contract.
+        ///
+        /// The caller must be registered with `add_trusted_spender`, `from` must have opted in
+        /// with `set_spender_opt_in`, and the spender's per-call and per-period caps apply.
         #[ink(message)]
         pub fn transfer_from_contract(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error> {
-            self._transfer_from_to(from, to, value, data)?;
+            let spender = Self::env().caller();
+            self.spend_as_trusted_spender(spender, from, value)?;
+            self.transfer_with_fee(from, to, value, data)
+        }
+
+        /// Burns `value` of `from`'s tokens on behalf of a trusted game contract.
+        ///
+        /// Same rules as `transfer_from_contract`: registered caller, opted-in owner, spending caps.
+        #[ink(message)]
+        pub fn burn_from_contract(&mut self, from: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let spender = Self::env().caller();
+            self.spend_as_trusted_spender(spender, from, value)?;
+            self._burn_from(from, value)
+        }
+
+        /// Sends each recipient its amount. Either every transfer succeeds or none does.
+        #[ink(message)]
+        pub fn batch_transfer(&mut self, recipients: Vec<(AccountId, Balance)>, data: Vec<u8>) -> Result<(), PSP22Error> {
+            if recipients.len() > MAX_BATCH_SIZE {
+                return Err(PSP22Error::Custom(String::from("BatchTooLarge")))
+            }
+            let from = Self::env().caller();
+            let total = recipients
+                .iter()
+                .try_fold(0 as Balance, |total, (_, value)| total.checked_add(*value))
+                .ok_or(PSP22Error::InsufficientBalance)?;
+            if self.balance_of(from) < total {
+                return Err(PSP22Error::InsufficientBalance)
+            }
+            for (to, value) in recipients {
+                self.transfer_with_fee(from, to, value, data.clone())?;
+            }
+            Ok(())
+        }
+
+        /// Starts an airdrop of `amount` tokens, taken from the caller, claimable until `expiry`.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn start_airdrop(
+            &mut self,
+            merkle_root: [u8; 32],
+            amount: Balance,
+            expiry: u64,
+            treasury: AccountId
+        ) -> Result<(), PSP22Error> {
+            if self.get_airdrop().is_some() {
+                return Err(PSP22Error::Custom(String::from("AirdropInProgress")))
+            }
+            if expiry <= Self::env().block_timestamp() {
+                return Err(PSP22Error::Custom(String::from("AirdropExpired")))
+            }
+            let caller = Self::env().caller();
+            let contract = Self::env().account_id();
+            self._transfer_from_to(caller, contract, amount, Vec::new())?;
+
+            self.airdrop.set(&Some(Airdrop { merkle_root, remaining: amount, expiry, treasury }));
+            self.airdrop_round.set(&(self.get_airdrop_round() + 1));
+            Ok(())
+        }
+
+        /// Claims the caller's `amount`, proven by the sibling hashes from its leaf up to the root.
+        #[ink(message)]
+        pub fn claim_airdrop(&mut self, amount: Balance, proof: Vec<[u8; 32]>) -> Result<(), PSP22Error> {
+            let account = Self::env().caller();
+            let mut airdrop = self
+                .get_airdrop()
+                .ok_or(PSP22Error::Custom(String::from("NoAirdrop")))?;
+
+            if Self::env().block_timestamp() > airdrop.expiry {
+                return Err(PSP22Error::Custom(String::from("AirdropExpired")))
+            }
+            if self.is_airdrop_claimed(account) {
+                return Err(PSP22Error::Custom(String::from("AlreadyClaimed")))
+            }
+            let leaf = Self::airdrop_leaf(account, amount);
+            if Self::merkle_root_of(leaf, &proof) != airdrop.merkle_root {
+                return Err(PSP22Error::Custom(String::from("InvalidProof")))
+            }
+            if amount > airdrop.remaining {
+                return Err(PSP22Error::InsufficientBalance)
+            }
+
+            airdrop.remaining -= amount;
+            self.airdrop.set(&Some(airdrop));
+            let round = self.get_airdrop_round();
+            self.airdrop_claims.insert(&(round, account), &true);
+
+            let contract = Self::env().account_id();
+            self._transfer_from_to(contract, account, amount, Vec::new())?;
+            Self::env().emit_event(AirdropClaimed { account, round, amount });
+            Ok(())
+        }
+
+        /// Ends an expired airdrop and sends the unclaimed tokens to its treasury. Anyone can call it.
+        #[ink(message)]
+        pub fn close_airdrop(&mut self) -> Result<(), PSP22Error> {
+            let airdrop = self
+                .get_airdrop()
+                .ok_or(PSP22Error::Custom(String::from("NoAirdrop")))?;
+            if Self::env().block_timestamp() <= airdrop.expiry {
+                return Err(PSP22Error::Custom(String::from("AirdropNotExpired")))
+            }
+
+            self.airdrop.set(&None);
+            if airdrop.remaining > 0 {
+                let contract = Self::env().account_id();
+                self._transfer_from_to(contract, airdrop.treasury, airdrop.remaining, Vec::new())?;
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_airdrop(&self) -> Option<Airdrop> {
+            self.airdrop.get().flatten()
+        }
+
+        #[ink(message)]
+        pub fn get_airdrop_round(&self) -> u32 {
+            self.airdrop_round.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn is_airdrop_claimed(&self, account: AccountId) -> bool {
+            self.airdrop_claims.get(&(self.get_airdrop_round(), account)).unwrap_or_default()
+        }
+
+        /// Blocks all token movement from and to `account`, e.g. a compromised or exploit account.
+        ///
+        /// `reason` is an off-chain defined code that explains why the account was frozen.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn freeze_account(&mut self, account: AccountId, reason: u8) -> Result<(), PSP22Error> {
+            self.frozen_accounts.insert(&account, &reason);
+            Self::env().emit_event(AccountFrozen { account, reason });
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn unfreeze_account(&mut self, account: AccountId) -> Result<(), PSP22Error> {
+            if !self.is_frozen(account) {
+                return Err(PSP22Error::Custom(String::from("AccountNotFrozen")))
+            }
+            self.frozen_accounts.remove(&account);
+            Self::env().emit_event(AccountUnfrozen { account });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_frozen(&self, account: AccountId) -> bool {
+            self.frozen_accounts.get(&account).is_some()
+        }
+
+        #[ink(message)]
+        pub fn get_freeze_reason(&self, account: AccountId) -> Option<u8> {
+            self.frozen_accounts.get(&account)
+        }
+
+        /// Takes `fee_rate` basis points of every transfer and sends them to `treasury`.
+        ///
+        /// A rate of 0 turns the fee off.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_transfer_fee(&mut self, fee_rate: u16, treasury: AccountId) -> Result<(), PSP22Error> {
+            if fee_rate > MAX_FEE_RATE {
+                return Err(PSP22Error::Custom(String::from("FeeRateTooHigh")))
+            }
+            self.fee_rate.set(&fee_rate);
+            self.fee_treasury.set(&Some(treasury));
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_fee_exempt(&mut self, account: AccountId, exempt: bool) -> Result<(), PSP22Error> {
+            if exempt {
+                self.fee_exempt.insert(&account, &true);
+            } else {
+                self.fee_exempt.remove(&account);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_fee_exempt(&self, account: AccountId) -> bool {
+            self.fee_exempt.get(&account).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_fee_rate(&self) -> u16 {
+            self.fee_rate.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_fee_treasury(&self) -> Option<AccountId> {
+            self.fee_treasury.get().flatten()
+        }
+
+        /// The fee taken when `from` sends `value` to `to`; the recipient gets the rest.
+        #[ink(message)]
+        pub fn get_transfer_fee(&self, from: AccountId, to: AccountId, value: Balance) -> Balance {
+            let treasury = match self.get_fee_treasury() {
+                Some(treasury) => treasury,
+                None => return 0,
+            };
+            if from == treasury || to == treasury || self.is_fee_exempt(from) || self.is_fee_exempt(to) {
+                return 0
+            }
+            // split so that large transfers can't overflow
+            let rate = self.get_fee_rate() as Balance;
+            value / 10000 * rate + value % 10000 * rate / 10000
+        }
+
+        #[ink(message)]
+        pub fn get_total_burned(&self) -> Balance {
+            self.total_burned.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn add_trusted_spender(
+            &mut self,
+            spender: AccountId,
+            max_per_call: Balance,
+            max_per_period: Balance,
+            period: u64
+        ) -> Result<(), PSP22Error> {
+            if period == 0 || max_per_call > max_per_period {
+                return Err(PSP22Error::Custom(String::from("InvalidSpendingLimits")))
+            }
+            self.trusted_spenders.insert(&spender, &TrustedSpender { max_per_call, max_per_period, period });
+            self.spending_windows.remove(&spender);
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn remove_trusted_spender(&mut self, spender: AccountId) -> Result<(), PSP22Error> {
+            if self.trusted_spenders.get(&spender).is_none() {
+                return Err(PSP22Error::Custom(String::from("NotTrustedSpender")))
+            }
+            self.trusted_spenders.remove(&spender);
+            self.spending_windows.remove(&spender);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_trusted_spender(&self, spender: AccountId) -> Option<TrustedSpender> {
+            self.trusted_spenders.get(&spender)
+        }
+
+        /// Returns how much `spender` can still move in its current period.
+        #[ink(message)]
+        pub fn get_remaining_spending(&self, spender: AccountId) -> Balance {
+            match self.trusted_spenders.get(&spender) {
+                Some(limits) => {
+                    let window = self.current_window(spender, &limits);
+                    limits.max_per_period.saturating_sub(window.spent)
+                }
+                None => 0,
+            }
+        }
+
+        /// Lets the caller allow (or forbid) a trusted spender to move the caller's tokens.
+        #[ink(message)]
+        pub fn set_spender_opt_in(&mut self, spender: AccountId, opted_in: bool) -> Result<(), PSP22Error> {
+            let owner = Self::env().caller();
+            if opted_in {
+                self.spender_opt_ins.insert(&(owner, spender), &true);
+            } else {
+                self.spender_opt_ins.remove(&(owner, spender));
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_opted_in(&self, owner: AccountId, spender: AccountId) -> bool {
+            self.spender_opt_ins.get(&(owner, spender)).unwrap_or_default()
+        }
+
+        /// Approves `spender` for `value` of `owner`'s tokens using a signature instead of an `approve` call.
+        ///
+        /// `signature` is `owner`'s 65 byte ECDSA signature of `get_permit_hash` for the
+        /// owner's current nonce. Anyone (e.g. a relayer) can submit it before `deadline`.
+        #[ink(message)]
+        pub fn permit(
+            &mut self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            deadline: u64,
+            signature: [u8; 65]
+        ) -> Result<(), PSP22Error> {
+            if Self::env().block_timestamp() > deadline {
+                return Err(PSP22Error::Custom(String::from("PermitExpired")))
+            }
+            let nonce = self.get_permit_nonce(owner);
+            let message_hash = self.get_permit_hash(owner, spender, value, nonce, deadline);
+            if self.recover_signer(&signature, &message_hash) != Some(owner) {
+                return Err(PSP22Error::Custom(String::from("InvalidSignature")))
+            }
+            self.permit_nonces.insert(&owner, &(nonce + 1));
+            self._approve_from_to(owner, spender, value)
+        }
+
+        /// Runs `permit` for the caller and spends the approval right away, so a game
+        /// contract or relayer can take a payment in one transaction.
+        #[ink(message)]
+        pub fn transfer_from_with_permit(
+            &mut self,
+            owner: AccountId,
+            to: AccountId,
+            value: Balance,
+            deadline: u64,
+            signature: [u8; 65],
+            data: Vec<u8>
+        ) -> Result<(), PSP22Error> {
+            let spender = Self::env().caller();
+            self.permit(owner, spender, value, deadline, signature)?;
+            self.transfer_from(owner, to, value, data)
+        }
+
+        #[ink(message)]
+        pub fn get_permit_nonce(&self, owner: AccountId) -> u64 {
+            self.permit_nonces.get(&owner).unwrap_or_default()
+        }
+
+        /// The 32 byte hash the owner has to sign for `permit`.
+        #[ink(message)]
+        pub fn get_permit_hash(
+            &self,
+            owner: AccountId,
+            spender: AccountId,
+            value: Balance,
+            nonce: u64,
+            deadline: u64
+        ) -> [u8; 32] {
+            let contract = Self::env().account_id();
+            let mut output = <hash::Blake2x256 as hash::HashOutput>::Type::default();
+            ink::env::hash_encoded::<hash::Blake2x256, _>(
+                &(PERMIT_DOMAIN, contract, owner, spender, value, nonce, deadline),
+                &mut output,
+            );
+            output
+        }
+
+        /// Gives the caller's voting power to `delegatee`, which can be the caller itself.
+        ///
+        /// Tokens only count as votes once they are delegated.
+        #[ink(message)]
+        pub fn delegate(&mut self, delegatee: AccountId) -> Result<(), PSP22Error> {
+            let delegator = Self::env().caller();
+            self.update_delegate(delegator, Some(delegatee));
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn undelegate(&mut self) -> Result<(), PSP22Error> {
+            let delegator = Self::env().caller();
+            self.update_delegate(delegator, None);
             Ok(())
         }
+
+        #[ink(message)]
+        pub fn get_delegate(&self, account: AccountId) -> Option<AccountId> {
+            self.delegates.get(&account)
+        }
+
+        #[ink(message)]
+        pub fn get_votes(&self, account: AccountId) -> Balance {
+            self.latest_checkpoint(&CheckpointKey::Votes(account)).map_or(0, |checkpoint| checkpoint.value)
+        }
+
+        /// Voting power of `account` at the end of `block`, which has to be in the past.
+        #[ink(message)]
+        pub fn get_past_votes(&self, account: AccountId, block: u32) -> Result<Balance, PSP22Error> {
+            self.value_at_block(CheckpointKey::Votes(account), block)
+        }
+
+        /// Voting power of `account` at `timestamp`, which has to be in the past.
+        #[ink(message)]
+        pub fn get_past_votes_at_time(&self, account: AccountId, timestamp: u64) -> Result<Balance, PSP22Error> {
+            self.value_at_time(CheckpointKey::Votes(account), timestamp)
+        }
+
+        #[ink(message)]
+        pub fn balance_of_at(&self, account: AccountId, block: u32) -> Result<Balance, PSP22Error> {
+            self.value_at_block(CheckpointKey::Balance(account), block)
+        }
+
+        #[ink(message)]
+        pub fn balance_of_at_time(&self, account: AccountId, timestamp: u64) -> Result<Balance, PSP22Error> {
+            self.value_at_time(CheckpointKey::Balance(account), timestamp)
+        }
+
+        #[ink(message)]
+        pub fn total_supply_at(&self, block: u32) -> Result<Balance, PSP22Error> {
+            self.value_at_block(CheckpointKey::TotalSupply, block)
+        }
+
+        #[ink(message)]
+        pub fn total_supply_at_time(&self, timestamp: u64) -> Result<Balance, PSP22Error> {
+            self.value_at_time(CheckpointKey::TotalSupply, timestamp)
+        }
+
+        /// Changes the display name shown by wallets and explorers.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_name(&mut self, name: Option<String>) -> Result<(), PSP22Error> {
+            self.metadata.name = name;
+            Ok(())
+        }
+
+        /// Changes which role administers `role`, e.g. to let a minter admin manage minters.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_role_admin(&mut self, role: RoleType, new_admin: RoleType) -> Result<(), AccessControlError> {
+            self._set_role_admin(role, new_admin);
+            Ok(())
+        }
+
+        /// Stops every transfer, mint and burn until `unpause` is called.
+        #[ink(message)]
+        #[modifiers(only_role(PAUSER))]
+        pub fn pause(&mut self) -> Result<(), PSP22Error> {
+            self._pause()
+        }
+
+        #[ink(message)]
+        #[modifiers(only_role(PAUSER))]
+        pub fn unpause(&mut self) -> Result<(), PSP22Error> {
+            self._unpause()
+        }
+
+        #[ink(message)]
+        pub fn get_minter_role(&self) -> RoleType {
+            MINTER
+        }
+
+        #[ink(message)]
+        pub fn get_pauser_role(&self) -> RoleType {
+            PAUSER
+        }
+
+        /// Replaces the contract code, keeping all storage. Call `migrate` afterwards.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), PSP22Error> {
+            ink::env::set_code_hash(&code_hash)
+                .map_err(|_| PSP22Error::Custom(String::from("UpgradeFailed")))?;
+            Ok(())
+        }
+
+        /// Brings stored data up to `STORAGE_VERSION`.
+        #[ink(message)]
+        #[modifiers(only_role(DEFAULT_ADMIN_ROLE))]
+        pub fn migrate(&mut self) -> Result<(), PSP22Error> {
+            while self.get_storage_version() < STORAGE_VERSION {
+                let from_version = self.get_storage_version();
+                self.migrate_from(from_version)?;
+                self.storage_version.set(&(from_version + 1));
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version.get().unwrap_or_default()
+        }
+
+        // internal function
+
+        // moves stored data from the `from_version` layout to the next one
+        fn migrate_from(&mut self, from_version: u32) -> Result<(), PSP22Error> {
+            match from_version {
+                // releases before version 1 had neither `set_code` nor an admin to call `migrate`,
+                // so a contract can never get here
+                0 => Err(PSP22Error::Custom(String::from("NotUpgradeable"))),
+                _ => Err(PSP22Error::Custom(String::from("UnknownStorageVersion"))),
+            }
+        }
+
+        // the transfer every user-facing path goes through, so fees are charged the same way everywhere
+        fn transfer_with_fee(&mut self, from: AccountId, to: AccountId, value: Balance, data: Vec<u8>) -> Result<(), PSP22Error> {
+            let fee = self.get_transfer_fee(from, to, value);
+            if fee == 0 {
+                return self._transfer_from_to(from, to, value, data)
+            }
+            if self.balance_of(from) < value {
+                return Err(PSP22Error::InsufficientBalance)
+            }
+
+            // set whenever the fee is not 0
+            let treasury = self.get_fee_treasury().unwrap_or(from);
+            self._transfer_from_to(from, to, value - fee, data)?;
+            self._transfer_from_to(from, treasury, fee, Vec::new())?;
+            Self::env().emit_event(TransferFee { from, to, value, fee, treasury });
+            Ok(())
+        }
+
+        pub fn airdrop_leaf(account: AccountId, amount: Balance) -> [u8; 32] {
+            let mut output = <hash::Keccak256 as hash::HashOutput>::Type::default();
+            ink::env::hash_encoded::<hash::Keccak256, _>(&(account, amount), &mut output);
+            output
+        }
+
+        // hashes `leaf` up the tree, pairing it with each proof node in sorted order
+        pub fn merkle_root_of(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+            proof.iter().fold(leaf, |node, sibling| {
+                let (left, right) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+                let mut input = [0u8; 64];
+                input[..32].copy_from_slice(&left);
+                input[32..].copy_from_slice(&right);
+                let mut output = <hash::Keccak256 as hash::HashOutput>::Type::default();
+                ink::env::hash_bytes::<hash::Keccak256>(&input, &mut output);
+                output
+            })
+        }
+
+        // moves the delegator's whole balance from its current delegate to `to_delegate`
+        fn update_delegate(&mut self, delegator: AccountId, to_delegate: Option<AccountId>) {
+            let from_delegate = self.delegates.get(&delegator);
+            match to_delegate {
+                Some(delegatee) => {
+                    self.delegates.insert(&delegator, &delegatee);
+                }
+                None => {
+                    self.delegates.remove(&delegator);
+                }
+            }
+            let balance = self.balance_of(delegator);
+            self.move_voting_power(from_delegate, to_delegate, balance);
+            Self::env().emit_event(DelegateChanged { delegator, from_delegate, to_delegate });
+        }
+
+        fn move_voting_power(&mut self, from: Option<AccountId>, to: Option<AccountId>, amount: Balance) {
+            if from == to || amount == 0 {
+                return
+            }
+            if let Some(delegate) = from {
+                let previous_votes = self.get_votes(delegate);
+                let new_votes = previous_votes.saturating_sub(amount);
+                self.write_checkpoint(CheckpointKey::Votes(delegate), new_votes);
+                Self::env().emit_event(DelegateVotesChanged { delegate, previous_votes, new_votes });
+            }
+            if let Some(delegate) = to {
+                let previous_votes = self.get_votes(delegate);
+                let new_votes = previous_votes.saturating_add(amount);
+                self.write_checkpoint(CheckpointKey::Votes(delegate), new_votes);
+                Self::env().emit_event(DelegateVotesChanged { delegate, previous_votes, new_votes });
+            }
+        }
+
+        // records `value` for the current block, replacing a checkpoint already written in this block
+        fn write_checkpoint(&mut self, key: CheckpointKey, value: Balance) {
+            let block = Self::env().block_number();
+            let timestamp = Self::env().block_timestamp();
+            let count = self.checkpoint_counts.get(&key).unwrap_or_default();
+
+            let index = match self.latest_checkpoint(&key) {
+                Some(latest) if latest.block == block => count - 1,
+                _ => {
+                    self.checkpoint_counts.insert(&key, &(count + 1));
+                    count
+                }
+            };
+            self.checkpoints.insert(&(key, index), &Checkpoint { block, timestamp, value });
+        }
+
+        fn latest_checkpoint(&self, key: &CheckpointKey) -> Option<Checkpoint> {
+            let count = self.checkpoint_counts.get(key).unwrap_or_default();
+            if count == 0 {
+                return None
+            }
+            self.checkpoints.get(&(key.clone(), count - 1))
+        }
+
+        fn value_at_block(&self, key: CheckpointKey, block: u32) -> Result<Balance, PSP22Error> {
+            // the current block can still change
+            if block >= Self::env().block_number() {
+                return Err(PSP22Error::Custom(String::from("FutureLookup")))
+            }
+            Ok(self.checkpoint_lookup(&key, |checkpoint| checkpoint.block <= block))
+        }
+
+        fn value_at_time(&self, key: CheckpointKey, timestamp: u64) -> Result<Balance, PSP22Error> {
+            if timestamp >= Self::env().block_timestamp() {
+                return Err(PSP22Error::Custom(String::from("FutureLookup")))
+            }
+            Ok(self.checkpoint_lookup(&key, |checkpoint| checkpoint.timestamp <= timestamp))
+        }
+
+        // value of the last checkpoint matching `is_before`, by binary search
+        fn checkpoint_lookup<F: Fn(&Checkpoint) -> bool>(&self, key: &CheckpointKey, is_before: F) -> Balance {
+            let mut low = 0;
+            let mut high = self.checkpoint_counts.get(key).unwrap_or_default();
+            while low < high {
+                let mid = low + (high - low) / 2;
+                let checkpoint = self.checkpoints.get(&(key.clone(), mid)).unwrap_or_default();
+                if is_before(&checkpoint) {
+                    low = mid + 1;
+                } else {
+                    high = mid;
+                }
+            }
+            if low == 0 {
+                0
+            } else {
+                self.checkpoints.get(&(key.clone(), low - 1)).map_or(0, |checkpoint| checkpoint.value)
+            }
+        }
+
+        // the account of the ECDSA key that signed `message_hash`
+        fn recover_signer(&self, signature: &[u8; 65], message_hash: &[u8; 32]) -> Option<AccountId> {
+            let mut public_key = [0u8; 33];
+            ink::env::ecdsa_recover(signature, message_hash, &mut public_key).ok()?;
+            let mut account = <hash::Blake2x256 as hash::HashOutput>::Type::default();
+            ink::env::hash_bytes::<hash::Blake2x256>(&public_key, &mut account);
+            Some(AccountId::from(account))
+        }
+
+        // counts `amount` against the unlocked emission, if there is a schedule
+        fn use_emission(&mut self, amount: Balance) -> Result<(), PSP22Error> {
+            let mut emission = match self.get_emission_schedule() {
+                Some(emission) => emission,
+                None => return Ok(()),
+            };
+            if amount > self.get_unlocked_emission() {
+                return Err(PSP22Error::Custom(String::from("EmissionNotUnlocked")))
+            }
+            emission.minted += amount;
+            self.emission.set(&Some(emission));
+            Ok(())
+        }
+
+        // the spending window of `spender`, restarted once its period has passed
+        fn current_window(&self, spender: AccountId, limits: &TrustedSpender) -> SpendingWindow {
+            let current_time = Self::env().block_timestamp();
+            match self.spending_windows.get(&spender) {
+                Some(window) if current_time < window.start.saturating_add(limits.period) => window,
+                _ => SpendingWindow { start: current_time, spent: 0 },
+            }
+        }
+
+        fn spend_as_trusted_spender(&mut self, spender: AccountId, owner: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let limits = self
+                .trusted_spenders
+                .get(&spender)
+                .ok_or(PSP22Error::Custom(String::from("NotTrustedSpender")))?;
+
+            if !self.is_opted_in(owner, spender) {
+                return Err(PSP22Error::Custom(String::from("NotOptedIn")))
+            }
+            if value > limits.max_per_call {
+                return Err(PSP22Error::Custom(String::from("PerCallLimitExceeded")))
+            }
+
+            let mut window = self.current_window(spender, &limits);
+            let spent = window
+                .spent
+                .checked_add(value)
+                .filter(|spent| *spent <= limits.max_per_period)
+                .ok_or(PSP22Error::Custom(String::from("PeriodLimitExceeded")))?;
+            window.spent = spent;
+            self.spending_windows.insert(&spender, &window);
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test;
+
+        type Event = <Psp22Contract as ::ink::reflect::ContractEventBase>::Type;
+
+        fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn set_caller(sender: AccountId) {
+            ink::env::test::set_caller::<Environment>(sender);
+        }
+        fn set_block_timestamp(timestamp: u64) {
+            ink::env::test::set_block_timestamp::<Environment>(timestamp);
+        }
+
+        fn recorded_events() -> Vec<test::EmittedEvent> {
+            test::recorded_events().collect()
+        }
+
+        fn assert_transfer_event(
+            event: &test::EmittedEvent,
+            expected_from: Option<AccountId>,
+            expected_to: Option<AccountId>,
+            expected_value: Balance,
+        ) {
+            // the event signature, `from` and `to`
+            assert_eq!(event.topics.len(), 3);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Transfer(Transfer { from, to, value }) = decoded_event {
+                assert_eq!(from, expected_from);
+                assert_eq!(to, expected_to);
+                assert_eq!(value, expected_value);
+            } else {
+                panic!("encountered unexpected event kind: expected a Transfer event")
+            }
+        }
+
+        fn assert_approval_event(
+            event: &test::EmittedEvent,
+            expected_owner: AccountId,
+            expected_spender: AccountId,
+            expected_value: Balance,
+        ) {
+            // the event signature, `owner` and `spender`
+            assert_eq!(event.topics.len(), 3);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            if let Event::Approval(Approval { owner, spender, value }) = decoded_event {
+                assert_eq!(owner, expected_owner);
+                assert_eq!(spender, expected_spender);
+                assert_eq!(value, expected_value);
+            } else {
+                panic!("encountered unexpected event kind: expected an Approval event")
+            }
+        }
+
+        // 1000 tokens minted to the caller, capped at 10000
+        fn new_contract(admin: AccountId) -> Psp22Contract {
+            Psp22Contract::new(
+                1000,
+                10000,
+                admin,
+                Some(String::from("Apple Coin")),
+                Some(String::from("APPLE")),
+                18
+            )
+        }
+
+        #[ink::test]
+        fn new_sets_up_admin_roles() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let contract = new_contract(accounts.bob);
+
+            assert_eq!(contract.balance_of(accounts.alice), 1000);
+            assert!(contract.has_role(DEFAULT_ADMIN_ROLE, accounts.bob));
+            assert!(contract.has_role(MINTER, accounts.bob));
+            assert!(contract.has_role(PAUSER, accounts.bob));
+            assert!(!contract.has_role(MINTER, accounts.alice));
+        }
+
+        #[ink::test]
+        fn new_sets_metadata() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let contract = new_contract(accounts.alice);
+
+            assert_eq!(contract.token_name(), Some(String::from("Apple Coin")));
+            assert_eq!(contract.token_symbol(), Some(String::from("APPLE")));
+            assert_eq!(contract.token_decimals(), 18);
+        }
+
+        #[ink::test]
+        fn set_name_works_only_for_admin() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.set_name(Some(String::from("Golden Apple"))).is_ok());
+            assert_eq!(contract.token_name(), Some(String::from("Golden Apple")));
+
+            set_caller(accounts.bob);
+            assert!(contract.set_name(None).is_err());
+            assert_eq!(contract.token_name(), Some(String::from("Golden Apple")));
+        }
+
+        #[ink::test]
+        fn migrate_works_only_for_admin() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+
+            set_caller(accounts.bob);
+            assert!(contract.set_code([0x1; 32]).is_err());
+            assert!(contract.migrate().is_err());
+
+            set_caller(accounts.alice);
+            assert!(contract.migrate().is_ok());
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+
+            // releases without a version can't be upgraded
+            contract.storage_version.set(&0);
+            assert_eq!(contract.migrate(), Err(PSP22Error::Custom(String::from("NotUpgradeable"))));
+        }
+
+        #[ink::test]
+        fn mint_by_minter_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.mint_to(accounts.bob, 100).is_ok());
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+            assert_eq!(contract.total_supply(), 1100);
+        }
+
+        #[ink::test]
+        fn mint_without_role_fails() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.mint_to(accounts.charlie, 100),
+                Err(AccessControlError::MissingRole.into())
+            );
+            assert_eq!(
+                contract.mint(accounts.charlie, 100),
+                Err(AccessControlError::MissingRole.into())
+            );
+            assert_eq!(contract.balance_of(accounts.charlie), 0);
+            assert_eq!(contract.total_supply(), 1000);
+        }
+
+        #[ink::test]
+        fn granted_minter_can_mint_until_revoked() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.grant_role(MINTER, accounts.charlie).is_ok());
+
+            set_caller(accounts.charlie);
+            assert!(contract.mint_to(accounts.charlie, 100).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(contract.revoke_role(MINTER, accounts.charlie).is_ok());
+
+            set_caller(accounts.charlie);
+            assert!(contract.mint_to(accounts.charlie, 100).is_err());
+            assert_eq!(contract.balance_of(accounts.charlie), 100);
+        }
+
+        #[ink::test]
+        fn grant_role_without_admin_fails() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.grant_role(MINTER, accounts.charlie),
+                Err(AccessControlError::MissingRole)
+            );
+            assert_eq!(
+                contract.set_role_admin(MINTER, MINTER),
+                Err(AccessControlError::MissingRole)
+            );
+            assert!(!contract.has_role(MINTER, accounts.charlie));
+        }
+
+        #[ink::test]
+        fn renounce_role_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.renounce_role(MINTER, accounts.alice).is_ok());
+            assert!(!contract.has_role(MINTER, accounts.alice));
+            assert!(contract.mint_to(accounts.alice, 100).is_err());
+        }
+
+        #[ink::test]
+        fn role_admin_can_be_delegated() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            // minters manage other minters from now on
+            assert!(contract.set_role_admin(MINTER, MINTER).is_ok());
+            assert_eq!(contract.get_role_admin(MINTER), MINTER);
+            assert!(contract.grant_role(MINTER, accounts.bob).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.grant_role(MINTER, accounts.charlie).is_ok());
+            assert!(contract.has_role(MINTER, accounts.charlie));
+        }
+
+        #[ink::test]
+        fn only_admin_can_register_trusted_spender() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.bob);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_err());
+            assert_eq!(contract.get_trusted_spender(accounts.bob), None);
+
+            set_caller(accounts.alice);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_ok());
+            assert_eq!(
+                contract.get_trusted_spender(accounts.bob),
+                Some(TrustedSpender { max_per_call: 100, max_per_period: 1000, period: 60 * 1000 })
+            );
+
+            assert!(contract.remove_trusted_spender(accounts.bob).is_ok());
+            assert_eq!(contract.get_trusted_spender(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn transfer_from_contract_requires_trusted_spender() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+
+            // bob is not registered
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer_from_contract(accounts.alice, accounts.bob, 10, Vec::new()),
+                Err(PSP22Error::Custom(String::from("NotTrustedSpender")))
+            );
+            assert_eq!(contract.balance_of(accounts.alice), 1000);
+        }
+
+        #[ink::test]
+        fn transfer_from_contract_requires_opt_in() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_ok());
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer_from_contract(accounts.alice, accounts.charlie, 10, Vec::new()),
+                Err(PSP22Error::Custom(String::from("NotOptedIn")))
+            );
+
+            set_caller(accounts.alice);
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+            assert!(contract.is_opted_in(accounts.alice, accounts.bob));
+
+            set_caller(accounts.bob);
+            assert!(contract.transfer_from_contract(accounts.alice, accounts.charlie, 10, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.charlie), 10);
+
+            set_caller(accounts.alice);
+            assert!(contract.set_spender_opt_in(accounts.bob, false).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.transfer_from_contract(accounts.alice, accounts.charlie, 10, Vec::new()).is_err());
+        }
+
+        #[ink::test]
+        fn transfer_from_contract_respects_spending_caps() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 150, 60 * 1000).is_ok());
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+
+            set_block_timestamp(1000);
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.transfer_from_contract(accounts.alice, accounts.charlie, 101, Vec::new()),
+                Err(PSP22Error::Custom(String::from("PerCallLimitExceeded")))
+            );
+            assert!(contract.transfer_from_contract(accounts.alice, accounts.charlie, 100, Vec::new()).is_ok());
+            assert_eq!(contract.get_remaining_spending(accounts.bob), 50);
+            assert_eq!(
+                contract.transfer_from_contract(accounts.alice, accounts.charlie, 60, Vec::new()),
+                Err(PSP22Error::Custom(String::from("PeriodLimitExceeded")))
+            );
+
+            // the next period starts with a fresh budget
+            set_block_timestamp(61 * 1000);
+            assert_eq!(contract.get_remaining_spending(accounts.bob), 150);
+            assert!(contract.transfer_from_contract(accounts.alice, accounts.charlie, 60, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.charlie), 160);
+        }
+
+        #[ink::test]
+        fn burn_own_tokens_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.burn(accounts.alice, 100).is_ok());
+            assert_eq!(contract.balance_of(accounts.alice), 900);
+            assert_eq!(contract.total_supply(), 900);
+            assert_eq!(contract.get_total_burned(), 100);
+        }
+
+        #[ink::test]
+        fn burn_from_needs_allowance() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.burn(accounts.alice, 100), Err(PSP22Error::InsufficientAllowance));
+
+            set_caller(accounts.alice);
+            assert!(contract.approve(accounts.bob, 150).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.burn(accounts.alice, 100).is_ok());
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 50);
+            assert_eq!(contract.balance_of(accounts.alice), 900);
+            assert_eq!(contract.get_total_burned(), 100);
+        }
+
+        #[ink::test]
+        fn burn_from_contract_works_for_trusted_spender() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.burn_from_contract(accounts.alice, 50).is_err());
+
+            set_caller(accounts.alice);
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.burn_from_contract(accounts.alice, 50).is_ok());
+            assert_eq!(contract.balance_of(accounts.alice), 950);
+            assert_eq!(contract.total_supply(), 950);
+            assert_eq!(contract.get_total_burned(), 50);
+        }
+
+        #[ink::test]
+        fn mint_above_max_supply_fails() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert_eq!(contract.get_max_supply(), 10000);
+            assert!(contract.mint_to(accounts.bob, 9000).is_ok());
+            assert_eq!(
+                contract.mint_to(accounts.bob, 1),
+                Err(PSP22Error::Custom(String::from("CapExceeded")))
+            );
+            assert_eq!(contract.total_supply(), 10000);
+        }
+
+        #[ink::test]
+        #[should_panic]
+        fn new_above_max_supply_fails() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            Psp22Contract::new(1001, 1000, accounts.alice, None, None, 18);
+        }
+
+        #[ink::test]
+        fn emission_schedule_limits_minting() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            // 100 tokens every minute, starting at 0
+            assert!(contract.set_emission_schedule(0, 60 * 1000, 100).is_ok());
+            assert!(contract.set_emission_schedule(0, 60 * 1000, 1000).is_err());
+
+            set_block_timestamp(59 * 1000);
+            assert_eq!(contract.get_unlocked_emission(), 0);
+            assert!(contract.mint_to(accounts.bob, 1).is_err());
+
+            set_block_timestamp(150 * 1000);
+            assert_eq!(contract.get_unlocked_emission(), 200);
+            assert!(contract.mint_to(accounts.bob, 50).is_ok());
+            assert_eq!(contract.get_unlocked_emission(), 150);
+            assert_eq!(
+                contract.mint_to(accounts.bob, 151),
+                Err(PSP22Error::Custom(String::from("EmissionNotUnlocked")))
+            );
+
+            assert_eq!(contract.release_emission(accounts.charlie), Ok(150));
+            assert_eq!(contract.balance_of(accounts.charlie), 150);
+            assert_eq!(contract.get_unlocked_emission(), 0);
+            assert_eq!(contract.get_emission_schedule().unwrap().minted, 200);
+        }
+
+        #[ink::test]
+        fn release_emission_needs_minter() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.set_emission_schedule(0, 60 * 1000, 100).is_ok());
+
+            set_block_timestamp(60 * 1000);
+            set_caller(accounts.bob);
+            assert!(contract.release_emission(accounts.bob).is_err());
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn pause_blocks_token_movement() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_ok());
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+            assert!(contract.approve(accounts.bob, 100).is_ok());
+
+            assert!(contract.pause().is_ok());
+            assert!(contract.paused());
+
+            let paused: Result<(), PSP22Error> = Err(PausableError::Paused.into());
+            assert_eq!(contract.transfer(accounts.bob, 10, Vec::new()), paused);
+            assert_eq!(contract.mint_to(accounts.bob, 10), paused);
+            assert_eq!(contract.burn(accounts.alice, 10), paused);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.transfer_from(accounts.alice, accounts.bob, 10, Vec::new()), paused);
+            assert_eq!(contract.transfer_from_contract(accounts.alice, accounts.bob, 10, Vec::new()), paused);
+
+            // queries still work
+            assert_eq!(contract.balance_of(accounts.alice), 1000);
+            assert_eq!(contract.total_supply(), 1000);
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 100);
+
+            set_caller(accounts.alice);
+            assert!(contract.unpause().is_ok());
+            assert!(!contract.paused());
+            assert!(contract.transfer(accounts.bob, 10, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.bob), 10);
+        }
+
+        #[ink::test]
+        fn pause_needs_pauser() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.pause(), Err(AccessControlError::MissingRole.into()));
+            assert!(!contract.paused());
+
+            set_caller(accounts.alice);
+            assert!(contract.grant_role(PAUSER, accounts.bob).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.pause().is_ok());
+            assert!(contract.paused());
+        }
+
+        #[ink::test]
+        fn pause_and_unpause_emit_events() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            let emitted = ink::env::test::recorded_events().count();
+
+            assert!(contract.pause().is_ok());
+            assert!(contract.unpause().is_ok());
+            assert_eq!(ink::env::test::recorded_events().count(), emitted + 2);
+        }
+
+        // the account `permit` recovers for `secret_key`
+        fn permit_account(secret_key: &secp256k1::SecretKey) -> AccountId {
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp256k1::Secp256k1::new(), secret_key);
+            let mut account = <hash::Blake2x256 as hash::HashOutput>::Type::default();
+            ink::env::hash_bytes::<hash::Blake2x256>(&public_key.serialize(), &mut account);
+            AccountId::from(account)
+        }
+
+        // signs `message_hash` the way `permit` expects: 64 signature bytes and the recovery id
+        fn sign_permit(secret_key: &secp256k1::SecretKey, message_hash: [u8; 32]) -> [u8; 65] {
+            let message = secp256k1::Message::from_slice(&message_hash).unwrap();
+            let (recovery_id, compact) = secp256k1::Secp256k1::new()
+                .sign_ecdsa_recoverable(&message, secret_key)
+                .serialize_compact();
+            let mut signature = [0u8; 65];
+            signature[..64].copy_from_slice(&compact);
+            signature[64] = recovery_id.to_i32() as u8;
+            signature
+        }
+
+        #[ink::test]
+        fn permit_with_owner_signature_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            let secret_key = secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+            let owner = permit_account(&secret_key);
+            assert!(contract.transfer(owner, 200, Vec::new()).is_ok());
+
+            let signature = sign_permit(&secret_key, contract.get_permit_hash(owner, accounts.bob, 100, 0, 1000));
+            set_caller(accounts.bob);
+            assert!(contract.permit(owner, accounts.bob, 100, 1000, signature).is_ok());
+            assert_eq!(contract.allowance(owner, accounts.bob), 100);
+            assert_eq!(contract.get_permit_nonce(owner), 1);
+
+            // the signature was for nonce 0, so it can't be used again
+            assert_eq!(
+                contract.permit(owner, accounts.bob, 100, 1000, signature),
+                Err(PSP22Error::Custom(String::from("InvalidSignature")))
+            );
+            assert_eq!(contract.get_permit_nonce(owner), 1);
+
+            assert!(contract.transfer_from(owner, accounts.charlie, 100, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(owner), 100);
+            assert_eq!(contract.balance_of(accounts.charlie), 100);
+            assert_eq!(contract.allowance(owner, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn transfer_from_with_permit_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            let secret_key = secp256k1::SecretKey::from_slice(&[7u8; 32]).unwrap();
+            let owner = permit_account(&secret_key);
+            assert!(contract.transfer(owner, 200, Vec::new()).is_ok());
+
+            let signature = sign_permit(&secret_key, contract.get_permit_hash(owner, accounts.bob, 150, 0, 1000));
+            set_caller(accounts.bob);
+            assert!(contract
+                .transfer_from_with_permit(owner, accounts.charlie, 150, 1000, signature, Vec::new())
+                .is_ok());
+            assert_eq!(contract.balance_of(accounts.charlie), 150);
+            assert_eq!(contract.get_permit_nonce(owner), 1);
+        }
+
+        #[ink::test]
+        fn permit_hash_depends_on_nonce() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let contract = new_contract(accounts.alice);
+
+            assert_eq!(contract.get_permit_nonce(accounts.alice), 0);
+            assert_ne!(
+                contract.get_permit_hash(accounts.alice, accounts.bob, 100, 0, 1000),
+                contract.get_permit_hash(accounts.alice, accounts.bob, 100, 1, 1000)
+            );
+        }
+
+        #[ink::test]
+        fn permit_after_deadline_fails() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_block_timestamp(2000);
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.permit(accounts.alice, accounts.bob, 100, 1000, [0u8; 65]),
+                Err(PSP22Error::Custom(String::from("PermitExpired")))
+            );
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn permit_with_invalid_signature_fails() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.permit(accounts.alice, accounts.bob, 100, 1000, [1u8; 65]),
+                Err(PSP22Error::Custom(String::from("InvalidSignature")))
+            );
+            assert!(contract
+                .transfer_from_with_permit(accounts.alice, accounts.bob, 100, 1000, [1u8; 65], Vec::new())
+                .is_err());
+            assert_eq!(contract.allowance(accounts.alice, accounts.bob), 0);
+            assert_eq!(contract.get_permit_nonce(accounts.alice), 0);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn balance_and_supply_checkpoints_work() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            // block 1
+            test::advance_block::<Environment>();
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert!(contract.mint_to(accounts.bob, 50).is_ok());
+
+            // block 2
+            test::advance_block::<Environment>();
+            assert_eq!(contract.balance_of_at(accounts.alice, 0), Ok(1000));
+            assert_eq!(contract.balance_of_at(accounts.bob, 0), Ok(0));
+            assert_eq!(contract.balance_of_at(accounts.alice, 1), Ok(900));
+            assert_eq!(contract.balance_of_at(accounts.bob, 1), Ok(150));
+            assert_eq!(contract.total_supply_at(0), Ok(1000));
+            assert_eq!(contract.total_supply_at(1), Ok(1050));
+
+            // the current block is not final yet
+            assert_eq!(
+                contract.balance_of_at(accounts.bob, 2),
+                Err(PSP22Error::Custom(String::from("FutureLookup")))
+            );
+        }
+
+        #[ink::test]
+        fn checkpoints_by_timestamp_work() {
+            let accounts = default_accounts();
+            set_block_timestamp(1000);
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            test::advance_block::<Environment>();
+            set_block_timestamp(5000);
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+
+            test::advance_block::<Environment>();
+            set_block_timestamp(9000);
+            assert_eq!(contract.balance_of_at_time(accounts.bob, 4999), Ok(0));
+            assert_eq!(contract.balance_of_at_time(accounts.bob, 5000), Ok(100));
+            assert_eq!(contract.total_supply_at_time(1000), Ok(1000));
+            assert!(contract.balance_of_at_time(accounts.bob, 9000).is_err());
+        }
+
+        #[ink::test]
+        fn delegation_moves_voting_power() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            // undelegated tokens don't vote
+            assert_eq!(contract.get_votes(accounts.alice), 0);
+
+            assert!(contract.delegate(accounts.alice).is_ok());
+            assert_eq!(contract.get_delegate(accounts.alice), Some(accounts.alice));
+            assert_eq!(contract.get_votes(accounts.alice), 1000);
+
+            // block 1
+            test::advance_block::<Environment>();
+            assert!(contract.delegate(accounts.charlie).is_ok());
+            assert_eq!(contract.get_votes(accounts.alice), 0);
+            assert_eq!(contract.get_votes(accounts.charlie), 1000);
+
+            // votes follow transfers
+            set_caller(accounts.bob);
+            assert!(contract.delegate(accounts.bob).is_ok());
+            set_caller(accounts.alice);
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert_eq!(contract.get_votes(accounts.charlie), 900);
+            assert_eq!(contract.get_votes(accounts.bob), 100);
+
+            // block 2
+            test::advance_block::<Environment>();
+            assert!(contract.undelegate().is_ok());
+            assert_eq!(contract.get_delegate(accounts.alice), None);
+            assert_eq!(contract.get_votes(accounts.charlie), 0);
+
+            // block 3
+            test::advance_block::<Environment>();
+            assert_eq!(contract.get_past_votes(accounts.alice, 0), Ok(1000));
+            assert_eq!(contract.get_past_votes(accounts.alice, 1), Ok(0));
+            assert_eq!(contract.get_past_votes(accounts.charlie, 1), Ok(900));
+            assert_eq!(contract.get_past_votes(accounts.charlie, 2), Ok(0));
+            assert_eq!(contract.get_past_votes(accounts.bob, 2), Ok(100));
+        }
+
+        #[ink::test]
+        fn new_emits_transfer_event() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            new_contract(accounts.alice);
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 1);
+            assert_transfer_event(&events[0], None, Some(accounts.alice), 1000);
+        }
+
+        #[ink::test]
+        fn transfer_and_approve_emit_events() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.transfer(accounts.bob, 10, Vec::new()).is_ok());
+            assert!(contract.approve(accounts.bob, 50).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.transfer_from(accounts.alice, accounts.charlie, 20, Vec::new()).is_ok());
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 5);
+            assert_transfer_event(&events[1], Some(accounts.alice), Some(accounts.bob), 10);
+            assert_approval_event(&events[2], accounts.alice, accounts.bob, 50);
+            // transfer_from lowers the allowance first
+            assert_approval_event(&events[3], accounts.alice, accounts.bob, 30);
+            assert_transfer_event(&events[4], Some(accounts.alice), Some(accounts.charlie), 20);
+        }
+
+        #[ink::test]
+        fn mint_and_burn_emit_events() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.mint_to(accounts.bob, 100).is_ok());
+            assert!(contract.burn(accounts.alice, 40).is_ok());
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 3);
+            assert_transfer_event(&events[1], None, Some(accounts.bob), 100);
+            assert_transfer_event(&events[2], Some(accounts.alice), None, 40);
+        }
+
+        #[ink::test]
+        fn transfer_from_contract_emits_event() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_ok());
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.transfer_from_contract(accounts.alice, accounts.charlie, 30, Vec::new()).is_ok());
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 2);
+            assert_transfer_event(&events[1], Some(accounts.alice), Some(accounts.charlie), 30);
+        }
+
+        #[ink::test]
+        fn failed_transfer_emits_no_event() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.transfer(accounts.bob, 1001, Vec::new()).is_err());
+            assert_eq!(recorded_events().len(), 1);
+        }
+
+        #[ink::test]
+        fn batch_transfer_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            let recipients = vec![(accounts.bob, 100), (accounts.charlie, 200), (accounts.bob, 50)];
+            assert!(contract.batch_transfer(recipients, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.alice), 650);
+            assert_eq!(contract.balance_of(accounts.bob), 150);
+            assert_eq!(contract.balance_of(accounts.charlie), 200);
+        }
+
+        #[ink::test]
+        fn batch_transfer_is_all_or_nothing() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            let recipients = vec![(accounts.bob, 600), (accounts.charlie, 500)];
+            assert_eq!(contract.batch_transfer(recipients, Vec::new()), Err(PSP22Error::InsufficientBalance));
+            assert_eq!(contract.balance_of(accounts.alice), 1000);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+
+            let recipients = vec![(accounts.bob, 1); MAX_BATCH_SIZE + 1];
+            assert!(contract.batch_transfer(recipients, Vec::new()).is_err());
+        }
+
+        // airdrop of 100 to bob, 200 to charlie and 300 to django held by frank's account
+        fn airdrop_tree() -> ([u8; 32], Vec<[u8; 32]>, Vec<[u8; 32]>, Vec<[u8; 32]>) {
+            let accounts = default_accounts();
+            let bob = Psp22Contract::airdrop_leaf(accounts.bob, 100);
+            let charlie = Psp22Contract::airdrop_leaf(accounts.charlie, 200);
+            let django = Psp22Contract::airdrop_leaf(accounts.django, 300);
+            let bob_charlie = Psp22Contract::merkle_root_of(bob, &[charlie]);
+            let root = Psp22Contract::merkle_root_of(bob_charlie, &[django]);
+            (root, vec![charlie, django], vec![bob, django], vec![bob_charlie])
+        }
+
+        fn new_airdrop_contract() -> Psp22Contract {
+            let accounts = default_accounts();
+            test::set_callee::<Environment>(accounts.frank);
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            let (root, _, _, _) = airdrop_tree();
+            assert!(contract.start_airdrop(root, 600, 100 * 1000, accounts.eve).is_ok());
+            contract
+        }
+
+        #[ink::test]
+        fn claim_airdrop_works() {
+            let accounts = default_accounts();
+            let mut contract = new_airdrop_contract();
+            let (_, bob_proof, charlie_proof, django_proof) = airdrop_tree();
+            assert_eq!(contract.balance_of(accounts.frank), 600);
+
+            set_caller(accounts.bob);
+            assert!(contract.claim_airdrop(100, bob_proof).is_ok());
+            set_caller(accounts.charlie);
+            assert!(contract.claim_airdrop(200, charlie_proof).is_ok());
+            set_caller(accounts.django);
+            assert!(contract.claim_airdrop(300, django_proof).is_ok());
+
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+            assert_eq!(contract.balance_of(accounts.charlie), 200);
+            assert_eq!(contract.balance_of(accounts.django), 300);
+            assert_eq!(contract.balance_of(accounts.frank), 0);
+            assert_eq!(contract.get_airdrop().unwrap().remaining, 0);
+            assert!(contract.is_airdrop_claimed(accounts.bob));
+        }
+
+        #[ink::test]
+        fn claim_airdrop_twice_fails() {
+            let accounts = default_accounts();
+            let mut contract = new_airdrop_contract();
+            let (_, bob_proof, _, _) = airdrop_tree();
+
+            set_caller(accounts.bob);
+            assert!(contract.claim_airdrop(100, bob_proof.clone()).is_ok());
+            assert_eq!(
+                contract.claim_airdrop(100, bob_proof),
+                Err(PSP22Error::Custom(String::from("AlreadyClaimed")))
+            );
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+        }
+
+        #[ink::test]
+        fn claim_airdrop_with_wrong_proof_fails() {
+            let accounts = default_accounts();
+            let mut contract = new_airdrop_contract();
+            let (_, bob_proof, charlie_proof, _) = airdrop_tree();
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.claim_airdrop(200, bob_proof),
+                Err(PSP22Error::Custom(String::from("InvalidProof")))
+            );
+            assert_eq!(
+                contract.claim_airdrop(200, charlie_proof),
+                Err(PSP22Error::Custom(String::from("InvalidProof")))
+            );
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn close_airdrop_returns_unclaimed_after_expiry() {
+            let accounts = default_accounts();
+            let mut contract = new_airdrop_contract();
+            let (_, bob_proof, charlie_proof, _) = airdrop_tree();
+
+            set_caller(accounts.bob);
+            assert!(contract.claim_airdrop(100, bob_proof).is_ok());
+            assert_eq!(
+                contract.close_airdrop(),
+                Err(PSP22Error::Custom(String::from("AirdropNotExpired")))
+            );
+
+            set_block_timestamp(100 * 1000 + 1);
+            set_caller(accounts.charlie);
+            assert_eq!(
+                contract.claim_airdrop(200, charlie_proof),
+                Err(PSP22Error::Custom(String::from("AirdropExpired")))
+            );
+            assert!(contract.close_airdrop().is_ok());
+            assert_eq!(contract.balance_of(accounts.eve), 500);
+            assert_eq!(contract.balance_of(accounts.frank), 0);
+            assert_eq!(contract.get_airdrop(), None);
+        }
+
+        #[ink::test]
+        fn set_transfer_fee_works_only_for_admin() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert_eq!(
+                contract.set_transfer_fee(MAX_FEE_RATE + 1, accounts.eve),
+                Err(PSP22Error::Custom(String::from("FeeRateTooHigh")))
+            );
+            assert!(contract.set_transfer_fee(100, accounts.eve).is_ok());
+            assert_eq!(contract.get_fee_rate(), 100);
+            assert_eq!(contract.get_fee_treasury(), Some(accounts.eve));
+
+            set_caller(accounts.bob);
+            assert!(contract.set_transfer_fee(0, accounts.bob).is_err());
+            assert!(contract.set_fee_exempt(accounts.bob, true).is_err());
+            assert_eq!(contract.get_fee_rate(), 100);
+        }
+
+        #[ink::test]
+        fn transfer_fee_goes_to_treasury() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            // 5%
+            assert!(contract.set_transfer_fee(500, accounts.eve).is_ok());
+
+            assert_eq!(contract.get_transfer_fee(accounts.alice, accounts.bob, 100), 5);
+            assert_eq!(contract.get_transfer_fee(accounts.alice, accounts.bob, Balance::MAX), Balance::MAX / 20);
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.alice), 900);
+            assert_eq!(contract.balance_of(accounts.bob), 95);
+            assert_eq!(contract.balance_of(accounts.eve), 5);
+            assert_eq!(contract.total_supply(), 1000);
+
+            let events = recorded_events();
+            assert_transfer_event(&events[1], Some(accounts.alice), Some(accounts.bob), 95);
+            assert_transfer_event(&events[2], Some(accounts.alice), Some(accounts.eve), 5);
+            let decoded_event = <Event as scale::Decode>::decode(&mut &events[3].data[..]).unwrap();
+            if let Event::TransferFee(TransferFee { from, to, value, fee, treasury }) = decoded_event {
+                assert_eq!((from, to, value, fee, treasury), (accounts.alice, accounts.bob, 100, 5, accounts.eve));
+            } else {
+                panic!("encountered unexpected event kind: expected a TransferFee event")
+            }
+        }
+
+        #[ink::test]
+        fn transfer_fee_is_the_same_on_every_path() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.set_transfer_fee(500, accounts.eve).is_ok());
+            assert!(contract.add_trusted_spender(accounts.bob, 100, 1000, 60 * 1000).is_ok());
+            assert!(contract.set_spender_opt_in(accounts.bob, true).is_ok());
+            assert!(contract.approve(accounts.bob, 100).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.transfer_from(accounts.alice, accounts.charlie, 100, Vec::new()).is_ok());
+            assert!(contract.transfer_from_contract(accounts.alice, accounts.charlie, 100, Vec::new()).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(contract.batch_transfer(vec![(accounts.charlie, 100)], Vec::new()).is_ok());
+
+            assert_eq!(contract.balance_of(accounts.alice), 700);
+            assert_eq!(contract.balance_of(accounts.charlie), 285);
+            assert_eq!(contract.balance_of(accounts.eve), 15);
+        }
+
+        #[ink::test]
+        fn fee_exempt_accounts_pay_no_fee() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.set_transfer_fee(500, accounts.eve).is_ok());
+            assert!(contract.set_fee_exempt(accounts.bob, true).is_ok());
+            assert!(contract.is_fee_exempt(accounts.bob));
+
+            // sending to an exempt account
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+
+            // sending from an exempt account
+            set_caller(accounts.bob);
+            assert!(contract.transfer(accounts.charlie, 100, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.charlie), 100);
+            assert_eq!(contract.balance_of(accounts.eve), 0);
+
+            // the fee is charged again once the exemption is gone
+            set_caller(accounts.alice);
+            assert!(contract.set_fee_exempt(accounts.bob, false).is_ok());
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.bob), 95);
+        }
+
+        #[ink::test]
+        fn frozen_account_can_not_send_or_receive() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+            assert!(contract.transfer(accounts.bob, 100, Vec::new()).is_ok());
+            assert!(contract.add_trusted_spender(accounts.charlie, 100, 1000, 60 * 1000).is_ok());
+            assert!(contract.set_spender_opt_in(accounts.charlie, true).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.approve(accounts.charlie, 100).is_ok());
+            assert!(contract.set_spender_opt_in(accounts.charlie, true).is_ok());
+
+            set_caller(accounts.alice);
+            assert!(contract.freeze_account(accounts.bob, 3).is_ok());
+            assert!(contract.is_frozen(accounts.bob));
+            assert_eq!(contract.get_freeze_reason(accounts.bob), Some(3));
+
+            let sender_frozen: Result<(), PSP22Error> = Err(PSP22Error::Custom(String::from("SenderFrozen")));
+            let recipient_frozen: Result<(), PSP22Error> = Err(PSP22Error::Custom(String::from("RecipientFrozen")));
+
+            assert_eq!(contract.transfer(accounts.bob, 10, Vec::new()), recipient_frozen);
+            assert_eq!(contract.mint_to(accounts.bob, 10), recipient_frozen);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.transfer(accounts.alice, 10, Vec::new()), sender_frozen);
+
+            set_caller(accounts.charlie);
+            assert_eq!(contract.transfer_from(accounts.bob, accounts.charlie, 10, Vec::new()), sender_frozen);
+            assert_eq!(contract.transfer_from_contract(accounts.bob, accounts.charlie, 10, Vec::new()), sender_frozen);
+            assert_eq!(contract.transfer_from_contract(accounts.alice, accounts.bob, 10, Vec::new()), recipient_frozen);
+            assert_eq!(contract.balance_of(accounts.bob), 100);
+
+            set_caller(accounts.alice);
+            assert!(contract.unfreeze_account(accounts.bob).is_ok());
+            assert!(!contract.is_frozen(accounts.bob));
+
+            set_caller(accounts.bob);
+            assert!(contract.transfer(accounts.alice, 10, Vec::new()).is_ok());
+            assert_eq!(contract.balance_of(accounts.bob), 90);
+        }
+
+        #[ink::test]
+        fn freeze_needs_admin() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            set_caller(accounts.bob);
+            assert!(contract.freeze_account(accounts.charlie, 1).is_err());
+            assert!(!contract.is_frozen(accounts.charlie));
+
+            set_caller(accounts.alice);
+            assert!(contract.freeze_account(accounts.charlie, 1).is_ok());
+
+            set_caller(accounts.bob);
+            assert!(contract.unfreeze_account(accounts.charlie).is_err());
+            assert!(contract.is_frozen(accounts.charlie));
+
+            set_caller(accounts.alice);
+            assert!(contract.unfreeze_account(accounts.bob).is_err());
+        }
+
+        #[ink::test]
+        fn freeze_and_unfreeze_emit_events() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = new_contract(accounts.alice);
+
+            assert!(contract.freeze_account(accounts.bob, 7).is_ok());
+            assert!(contract.unfreeze_account(accounts.bob).is_ok());
+
+            let events = recorded_events();
+            assert_eq!(events.len(), 3);
+            let frozen = <Event as scale::Decode>::decode(&mut &events[1].data[..]).unwrap();
+            if let Event::AccountFrozen(AccountFrozen { account, reason }) = frozen {
+                assert_eq!((account, reason), (accounts.bob, 7));
+            } else {
+                panic!("encountered unexpected event kind: expected an AccountFrozen event")
+            }
+            let unfrozen = <Event as scale::Decode>::decode(&mut &events[2].data[..]).unwrap();
+            assert!(matches!(unfrozen, Event::AccountUnfrozen(AccountUnfrozen { account }) if account == accounts.bob));
+        }
     }
-}
\ No newline at end of file
+}
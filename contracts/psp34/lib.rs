@@ -11,6 +11,7 @@ pub mod my_psp34_mintable {
             },
             psp34::extensions::{
                 enumerable::*,
+                metadata::*,
                 mintable::*,
             },
         },
@@ -28,8 +29,19 @@ pub mod my_psp34_mintable {
     
     use ink::env::hash;
 
+    use ink::storage::{
+        traits::ManualKey,
+        Lazy,
+    };
+
     use ink::prelude::string::ToString;
 
+    use ink::prelude::{
+        format,
+        vec,
+        vec::Vec,
+    };
+
     // use ink::prelude::{
     //     string::ToString,
     //     vec::Vec,
@@ -37,6 +49,22 @@ pub mod my_psp34_mintable {
 
     use core::{time::Duration};
 
+    // bump this and add arms to `migrate_token` and `migrate_contract` whenever stored data changes shape
+    pub const STORAGE_VERSION: u32 = 10;
+
+    pub const MAX_CONDITION_TIERS: usize = 16;
+
+    // attributes computed from the pet's live status instead of read from storage
+    pub const LIVE_ATTRIBUTES: [&[u8]; 5] = [b"hungry", b"health", b"happy", b"condition", b"level"];
+
+    pub const MAX_LEVEL_THRESHOLDS: usize = 100;
+
+    // in milliseconds
+    pub const ONE_DAY: u64 = 24 * 60 * 60 * 1000;
+
+    // happy gained by playing
+    pub const PLAY_HAPPY: u32 = 10;
+
     #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
     #[cfg_attr(
         feature = "std",
@@ -48,6 +76,559 @@ pub mod my_psp34_mintable {
         pub happy: u32,
     }
 
+    // what is stored for each pet since storage version 3
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PetStatus {
+        pub status: Status,
+        // when `status` was last brought up to date; decay counts from here
+        pub last_updated: u64,
+    }
+
+    // which random branch eating an apple took
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum EatOutcome {
+        // +30 to every status
+        Normal,
+        // health and happy set to 100, hungry to 0
+        Full,
+        // +50 to every status
+        Lucky,
+        // health and happy set to 0, hungry to 80
+        Death,
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum DecayDirection {
+        Increase,
+        Decrease,
+    }
+
+    // how one stat changes every decay interval
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StatDecay {
+        pub rate: u32,
+        pub direction: DecayDirection,
+    }
+
+    impl StatDecay {
+        // intervals until `value` reaches `target`, if it ever does
+        pub fn intervals_until(&self, value: u32, target: u32) -> Option<u64> {
+            let distance = match self.direction {
+                _ if value == target => return Some(0),
+                DecayDirection::Increase if value < target => target - value,
+                DecayDirection::Decrease if value > target => value - target,
+                _ => return None,
+            };
+            if self.rate == 0 {
+                return None
+            }
+            Some((distance as u64 + self.rate as u64 - 1) / self.rate as u64)
+        }
+
+        // `value` after `intervals` decay intervals
+        pub fn apply(&self, value: u32, intervals: u64) -> u32 {
+            let change = u32::try_from(intervals.saturating_mul(self.rate as u64)).unwrap_or(u32::MAX);
+            match self.direction {
+                DecayDirection::Increase => value.saturating_add(change),
+                DecayDirection::Decrease => value.saturating_sub(change),
+            }
+        }
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct DecayConfig {
+        // in milliseconds
+        pub interval: u64,
+        pub hungry: StatDecay,
+        pub health: StatDecay,
+        pub happy: StatDecay,
+    }
+
+    impl Default for DecayConfig {
+        // hungry rises and health and happy fall by 5 every minute
+        fn default() -> Self {
+            Self {
+                interval: 60 * 1000,
+                hungry: StatDecay { rate: 5, direction: DecayDirection::Increase },
+                health: StatDecay { rate: 5, direction: DecayDirection::Decrease },
+                happy: StatDecay { rate: 5, direction: DecayDirection::Decrease },
+            }
+        }
+    }
+
+    // inclusive range a stat is kept in
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StatBounds {
+        pub min: u32,
+        pub max: u32,
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct StatCaps {
+        pub hungry: StatBounds,
+        pub health: StatBounds,
+        pub happy: StatBounds,
+    }
+
+    impl StatCaps {
+        pub fn is_valid(&self) -> bool {
+            [self.hungry, self.health, self.happy].iter().all(|bounds| bounds.min <= bounds.max)
+        }
+
+        pub fn clamp(&self, status: Status) -> Status {
+            Status {
+                hungry: status.hungry.clamp(self.hungry.min, self.hungry.max),
+                health: status.health.clamp(self.health.min, self.health.max),
+                happy: status.happy.clamp(self.happy.min, self.happy.max),
+            }
+        }
+
+        // starving or out of health
+        pub fn is_critical(&self, status: &Status) -> bool {
+            status.health <= self.health.min || status.hungry >= self.hungry.max
+        }
+    }
+
+    impl Default for StatCaps {
+        fn default() -> Self {
+            let bounds = StatBounds { min: 0, max: 100 };
+            Self { hungry: bounds, health: bounds, happy: bounds }
+        }
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct LifecycleConfig {
+        // below this health a pet is sick
+        pub sick_health: u32,
+        // how long a pet stays critical before it dies, in milliseconds
+        pub death_delay: u64,
+        // game money paid for a revive
+        pub revive_price: u64,
+        // apples paid for a revive instead
+        pub revive_apples: u16,
+    }
+
+    impl Default for LifecycleConfig {
+        fn default() -> Self {
+            Self {
+                sick_health: 30,
+                death_delay: 60 * 60 * 1000,
+                revive_price: 200,
+                revive_apples: 3,
+            }
+        }
+    }
+
+    // xp granted for caring for a pet
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct XpRewards {
+        pub eat: u64,
+        pub play: u64,
+        // granted on the first care of a day, times the streak of days in a row
+        pub daily_care: u64,
+        // the streak multiplier stops growing here
+        pub max_streak: u32,
+    }
+
+    impl Default for XpRewards {
+        fn default() -> Self {
+            Self {
+                eat: 10,
+                play: 5,
+                daily_care: 20,
+                max_streak: 7,
+            }
+        }
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum Stage {
+        #[default]
+        Egg,
+        Baby,
+        Adult,
+        Elder,
+    }
+
+    impl Stage {
+        pub fn next(&self) -> Option<Stage> {
+            match self {
+                Stage::Egg => Some(Stage::Baby),
+                Stage::Baby => Some(Stage::Adult),
+                Stage::Adult => Some(Stage::Elder),
+                Stage::Elder => None,
+            }
+        }
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct BreedConfig {
+        // game money paid per breeding
+        pub price: u64,
+        // PSP22 paid per breeding instead, split between burning and the owner like `buy_game_money`
+        pub psp22_price: Balance,
+        // time a parent must wait between breedings, in milliseconds
+        pub cooldown: u64,
+        // breedings allowed per parent
+        pub max_breeds: u32,
+    }
+
+    impl Default for BreedConfig {
+        fn default() -> Self {
+            Self {
+                price: 200,
+                psp22_price: 500,
+                cooldown: ONE_DAY,
+                max_breeds: 5,
+            }
+        }
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum BreedPayment {
+        GameMoney,
+        // PSP22 of the given contract, which has to be the payment token once one is set
+        Psp22(AccountId),
+    }
+
+    // where a bred pet comes from
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Lineage {
+        pub parent_a: u64,
+        pub parent_b: u64,
+        // one more than the highest generation of the parents; claimed pets are generation 0
+        pub generation: u32,
+    }
+
+    // what a pet needs to evolve out of a stage
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct EvolutionRequirement {
+        pub min_level: u32,
+        // index of the lowest condition tier allowed
+        pub min_condition: u32,
+        // time since minting, in milliseconds
+        pub min_age: u64,
+        // game money paid by the caller
+        pub price: u64,
+    }
+
+    // consecutive days a pet was cared for
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct CareStreak {
+        // the last day of the streak, counted from the unix epoch
+        pub day: u64,
+        pub streak: u32,
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PetState {
+        Healthy,
+        // health below `sick_health`
+        Sick,
+        // health at its minimum or hungry at its maximum
+        Critical,
+        // critical for longer than `death_delay`, until revived
+        Dead,
+    }
+
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum RevivePayment {
+        GameMoney,
+        Apples,
+    }
+
+    // a condition a pet is in once its total status reaches `min_score`
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct ConditionTier {
+        pub min_score: u32,
+        pub label: String,
+        pub base_uri: String,
+    }
+
+    // which of the condition uris changed
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum UriKind {
+        Bad,
+        Normal,
+        Good,
+    }
+
+    // where `token_uri` points to
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, Copy, PartialEq, Eq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum MetadataMode {
+        // the condition uri followed by the token id
+        #[default]
+        Ipfs,
+        // a base64 JSON data uri built from the live status
+        OnChain,
+    }
+
+    // colors and extra layers of the on-chain pet image
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct SvgConfig {
+        // background color per condition, from bad to good; the last one is used past the end
+        pub backgrounds: Vec<String>,
+        pub body: String,
+        pub hungry_bar: String,
+        pub health_bar: String,
+        pub happy_bar: String,
+        // svg elements drawn over the pet, in order
+        pub layers: Vec<String>,
+    }
+
+    impl Default for SvgConfig {
+        fn default() -> Self {
+            Self {
+                backgrounds: vec![
+                    String::from("#d9d9d9"),
+                    String::from("#fff2b3"),
+                    String::from("#c8f7c5"),
+                ],
+                body: String::from("#f4a261"),
+                hungry_bar: String::from("#e76f51"),
+                health_bar: String::from("#2a9d8f"),
+                happy_bar: String::from("#e9c46a"),
+                layers: Vec::new(),
+            }
+        }
+    }
+
+    #[ink(event)]
+    pub struct NftClaimed {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct ApplePurchased {
+        #[ink(topic)]
+        account: AccountId,
+        price: u64,
+        // apples the account has afterwards
+        apples: u16,
+    }
+
+    #[ink(event)]
+    pub struct AppleEaten {
+        #[ink(topic)]
+        account: AccountId,
+        #[ink(topic)]
+        token_id: u64,
+        outcome: EatOutcome,
+    }
+
+    #[ink(event)]
+    pub struct StatusChanged {
+        #[ink(topic)]
+        token_id: u64,
+        status: Status,
+    }
+
+    #[ink(event)]
+    pub struct DailyBonusClaimed {
+        #[ink(topic)]
+        account: AccountId,
+        amount: u64,
+    }
+
+    #[ink(event)]
+    pub struct GameMoneyBought {
+        #[ink(topic)]
+        buyer: AccountId,
+        #[ink(topic)]
+        to: AccountId,
+        // PSP22 paid, including the burned part
+        paid: Balance,
+        burned: Balance,
+        game_money: u64,
+    }
+
+    #[ink(event)]
+    pub struct MoneyStaked {
+        #[ink(topic)]
+        account: AccountId,
+        amount: u64,
+    }
+
+    #[ink(event)]
+    pub struct MoneyWithdrawn {
+        #[ink(topic)]
+        account: AccountId,
+        amount: u64,
+    }
+
+    #[ink(event)]
+    pub struct UriChanged {
+        kind: UriKind,
+        uri: String,
+    }
+
+    #[ink(event)]
+    pub struct StatCapsChanged {
+        caps: StatCaps,
+    }
+
+    #[ink(event)]
+    pub struct LifecycleConfigChanged {
+        config: LifecycleConfig,
+    }
+
+    #[ink(event)]
+    pub struct PetPlayed {
+        #[ink(topic)]
+        token_id: u64,
+    }
+
+    #[ink(event)]
+    pub struct XpGained {
+        #[ink(topic)]
+        token_id: u64,
+        amount: u64,
+        xp: u64,
+    }
+
+    #[ink(event)]
+    pub struct PetBred {
+        #[ink(topic)]
+        owner: AccountId,
+        parent_a: u64,
+        parent_b: u64,
+        #[ink(topic)]
+        child: u64,
+    }
+
+    #[ink(event)]
+    pub struct BreedConfigChanged {
+        config: BreedConfig,
+    }
+
+    #[ink(event)]
+    pub struct PetEvolved {
+        #[ink(topic)]
+        token_id: u64,
+        stage: Stage,
+    }
+
+    #[ink(event)]
+    pub struct LevelUp {
+        #[ink(topic)]
+        token_id: u64,
+        level: u32,
+    }
+
+    #[ink(event)]
+    pub struct LevelConfigChanged {
+        thresholds: Vec<u64>,
+        cap_bonus: u32,
+    }
+
+    #[ink(event)]
+    pub struct XpRewardsChanged {
+        rewards: XpRewards,
+    }
+
+    #[ink(event)]
+    pub struct PetDied {
+        #[ink(topic)]
+        token_id: u64,
+        time: u64,
+    }
+
+    #[ink(event)]
+    pub struct PetRevived {
+        #[ink(topic)]
+        token_id: u64,
+        #[ink(topic)]
+        payer: AccountId,
+        payment: RevivePayment,
+    }
+
+    #[ink(event)]
+    pub struct DecayConfigChanged {
+        config: DecayConfig,
+    }
+
+    #[ink(event)]
+    pub struct ConditionTiersChanged {
+        tiers: Vec<ConditionTier>,
+    }
+
+    #[ink(event)]
+    pub struct MetadataModeChanged {
+        mode: MetadataMode,
+    }
+
     #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
     #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
     pub enum ContractError {
@@ -59,6 +640,27 @@ pub mod my_psp34_mintable {
         TimeHasNotPassed,
         AlreadyHadOneNft,
         NumberOverflowError,
+        OwnableError,
+        InvalidConditionTiers,
+        InvalidDecayConfig,
+        InvalidStatCaps,
+        PetIsDead,
+        PetIsNotDead,
+        InvalidLevelThresholds,
+        InvalidEvolutionRequirements,
+        MaxStageReached,
+        LevelTooLow,
+        ConditionTooLow,
+        InvalidParents,
+        NotApproved,
+        BreedingCooldown,
+        MaxBreedsReached,
+        InvalidBurnRate,
+        ReadOnlyAttribute,
+        UpgradeFailed,
+        UnknownStorageVersion,
+        UnknownPaymentToken,
+        InvalidMigrationBatch,
     }
 
     impl From<PSP22Error> for ContractError {
@@ -72,6 +674,12 @@ pub mod my_psp34_mintable {
             Self::PSP34Error
         }
     }
+
+    impl From<OwnableError> for ContractError {
+        fn from(_: OwnableError) -> Self {
+            Self::OwnableError
+        }
+    }
     
 
     #[derive(Default, Storage)]
@@ -86,11 +694,10 @@ pub mod my_psp34_mintable {
         #[storage_field]
         ownable: ownable::Data,
 
-        // pub asset_status: Mapping<Id, Status>,
+        // statuses in the layout before storage version 3, moved into `pet_status` when migrating
         pub asset_status: Mapping<Id, Status>,
 
-        // this is three pattern uri
-        
+        // the uris of storage version 1, moved into `condition_tiers` when migrating to version 2
         pub normal_uri: String,
         pub good_uri: String,
         pub bad_uri: String,
@@ -118,6 +725,98 @@ pub mod my_psp34_mintable {
 
         // tokenId
         pub current_token_id: u64,
+
+        // Everything above is the first release, whose plain fields share the root cell.
+        // Later fields must be mappings, `Lazy` cells or openbrush data under its own key,
+        // or the root cell no longer decodes after `set_code` and even `migrate` can't run.
+
+        #[storage_field]
+        metadata: metadata::Data,
+
+        // status of each pet and when it was last brought up to date
+        pub pet_status: Mapping<Id, PetStatus>,
+
+        // share of every PSP22 payment that is burned, in basis points (10000 = 100%)
+        pub burn_rate: Lazy<u16, ManualKey<{ ink::selector_id!("burn_rate") }>>,
+
+        // PSP22 burned through game payments
+        pub total_burned: Lazy<Balance, ManualKey<{ ink::selector_id!("total_burned") }>>,
+
+        // version of the layout the stored data is in; unset in the first release
+        pub storage_version: Lazy<u32, ManualKey<{ ink::selector_id!("storage_version") }>>,
+
+        // tokens, in enumeration order, already migrated to the next storage version
+        pub migration_cursor: Lazy<u64, ManualKey<{ ink::selector_id!("migration_cursor") }>>,
+
+        // conditions ordered by `min_score`, the first one starting at 0
+        pub condition_tiers: Lazy<Vec<ConditionTier>, ManualKey<{ ink::selector_id!("condition_tiers") }>>,
+
+        // whether `token_uri` returns ipfs or on-chain metadata
+        pub metadata_mode: Lazy<MetadataMode, ManualKey<{ ink::selector_id!("metadata_mode") }>>,
+
+        // description in the on-chain metadata
+        pub description: Lazy<String, ManualKey<{ ink::selector_id!("description") }>>,
+
+        // how the on-chain image is drawn
+        pub svg_config: Lazy<SvgConfig, ManualKey<{ ink::selector_id!("svg_config") }>>,
+
+        // how statuses change over time
+        pub decay_config: Lazy<DecayConfig, ManualKey<{ ink::selector_id!("decay_config") }>>,
+
+        // range each stat is kept in
+        pub stat_caps: Lazy<StatCaps, ManualKey<{ ink::selector_id!("stat_caps") }>>,
+
+        // when pets get sick, die and what reviving them costs
+        pub lifecycle: Lazy<LifecycleConfig, ManualKey<{ ink::selector_id!("lifecycle") }>>,
+
+        // when a critical pet became critical, recorded whenever it is settled
+        pub critical_since: Mapping<Id, u64>,
+
+        // when a dead pet died
+        pub died_at: Mapping<Id, u64>,
+
+        // last played time
+        pub last_played: Mapping<Id, u64>,
+
+        // experience of each pet
+        pub xp: Mapping<Id, u64>,
+
+        pub care_streaks: Mapping<Id, CareStreak>,
+
+        pub xp_rewards: Lazy<XpRewards, ManualKey<{ ink::selector_id!("xp_rewards") }>>,
+
+        // xp needed for level 2, 3 and so on, increasing
+        pub level_thresholds: Lazy<Vec<u64>, ManualKey<{ ink::selector_id!("level_thresholds") }>>,
+
+        // added to the health and happy maximum for every level above 1
+        pub level_cap_bonus: Lazy<u32, ManualKey<{ ink::selector_id!("level_cap_bonus") }>>,
+
+        // mint time of each pet
+        pub born_at: Mapping<Id, u64>,
+
+        // pets without an entry are eggs
+        pub stages: Mapping<Id, Stage>,
+
+        // requirements to leave the egg, baby and adult stages, in that order
+        pub evolution_requirements: Lazy<Vec<EvolutionRequirement>, ManualKey<{ ink::selector_id!("evolution_requirements") }>>,
+
+        // base uri per stage and condition, used instead of the condition tier's when set
+        pub stage_uris: Mapping<(Stage, u32), String>,
+
+        // price, cooldown and limit of breeding
+        pub breed_config: Lazy<BreedConfig, ManualKey<{ ink::selector_id!("breed_config") }>>,
+
+        // last time each pet bred
+        pub last_bred: Mapping<Id, u64>,
+
+        // times each pet bred
+        pub breed_count: Mapping<Id, u32>,
+
+        // parents of bred pets
+        pub lineage: Mapping<Id, Lineage>,
+
+        // the only PSP22 contract accepted as payment
+        pub payment_token: Lazy<AccountId, ManualKey<{ ink::selector_id!("payment_token") }>>,
     }
 
     impl PSP34 for Contract {}
@@ -126,17 +825,53 @@ pub mod my_psp34_mintable {
 
     impl PSP34Enumerable for Contract {}
 
+    impl psp34::Transfer for Contract {
+        fn _after_token_transfer(
+            &mut self,
+            from: Option<&AccountId>,
+            _to: Option<&AccountId>,
+            id: &Id,
+        ) -> Result<(), PSP34Error> {
+            if from.is_none() {
+                self.born_at.insert(id, &Self::env().block_timestamp());
+            }
+            Ok(())
+        }
+    }
+
+    impl PSP34Metadata for Contract {
+        /// Returns `key` of `id`. The keys in `LIVE_ATTRIBUTES` are computed from the current status.
+        #[ink(message)]
+        fn get_attribute(&self, id: Id, key: String) -> Option<String> {
+            if let Id::U64(token_id) = id {
+                if LIVE_ATTRIBUTES.contains(&key.as_slice()) {
+                    return self.get_live_attribute(token_id, &key)
+                }
+            }
+            self.metadata.attributes.get(&(&id, &key))
+        }
+    }
+
     impl Contract {
         /// The constructor
         #[ink(constructor)]
         pub fn new() -> Self {
-            Self::default()
+            let mut instance = Self::default();
+            instance.condition_tiers.set(&Self::default_condition_tiers());
+            instance.set_default_levels();
+            instance.evolution_requirements.set(&Self::default_evolution_requirements());
+            instance.storage_version.set(&STORAGE_VERSION);
+            instance
         }
 
         #[ink(constructor)]
         pub fn new_with_owner(owner: AccountId) -> Self {
             let mut instance = Self::default();
             instance.set_owner(owner);
+            instance.condition_tiers.set(&Self::default_condition_tiers());
+            instance.set_default_levels();
+            instance.evolution_requirements.set(&Self::default_evolution_requirements());
+            instance.storage_version.set(&STORAGE_VERSION);
             instance
         }
 
@@ -155,46 +890,71 @@ pub mod my_psp34_mintable {
             Ok(())
         }
     
+        /// Replaces the condition tiers. They must start at 0 and be ordered by strictly increasing `min_score`.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_condition_tiers(&mut self, tiers: Vec<ConditionTier>) -> Result<(), ContractError> {
+            if tiers.is_empty() || tiers.len() > MAX_CONDITION_TIERS || tiers[0].min_score != 0 {
+                return Err(ContractError::InvalidConditionTiers)
+            }
+            if tiers.windows(2).any(|pair| pair[0].min_score >= pair[1].min_score) {
+                return Err(ContractError::InvalidConditionTiers)
+            }
+            self.condition_tiers.set(&tiers);
+            Self::env().emit_event(ConditionTiersChanged { tiers });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_condition_tiers(&self) -> Vec<ConditionTier> {
+            self.condition_tiers.get().unwrap_or_default()
+        }
+
         // normal
         #[ink(message)]
         #[modifiers(only_owner)]
         pub fn set_normal_uri(&mut self, normal_uri:String) -> Result<(), PSP34Error> {
-            self.normal_uri = normal_uri;
+            self.set_tier_uri(b"normal", normal_uri.clone())?;
+            Self::env().emit_event(UriChanged { kind: UriKind::Normal, uri: normal_uri });
             Ok(())
         }
 
         #[ink(message)]
         pub fn get_normal_uri(&self) -> String {
-            self.normal_uri.clone()
+            self.get_tier_uri(b"normal")
         }
 
         // good
         #[ink(message)]
         #[modifiers(only_owner)]
         pub fn set_good_uri(&mut self, good_uri:String) -> Result<(), PSP34Error> {
-            self.good_uri = good_uri;
+            self.set_tier_uri(b"good", good_uri.clone())?;
+            Self::env().emit_event(UriChanged { kind: UriKind::Good, uri: good_uri });
             Ok(())
         }
 
         #[ink(message)]
         pub fn get_good_uri(&self) -> String {
-            self.good_uri.clone()
+            self.get_tier_uri(b"good")
         }
 
         // bad
         #[ink(message)]
         #[modifiers(only_owner)]
         pub fn set_bad_uri(&mut self, bad_uri:String) -> Result<(), PSP34Error>{
-            self.bad_uri = bad_uri;
+            self.set_tier_uri(b"bad", bad_uri.clone())?;
+            Self::env().emit_event(UriChanged { kind: UriKind::Bad, uri: bad_uri });
             Ok(())
         }
 
         #[ink(message)]
         pub fn get_bad_uri(&self) -> String {
-            self.bad_uri.clone()
+            self.get_tier_uri(b"bad")
         }
 
+        /// Overwrites the status of a pet. Only the contract owner can call it.
         #[ink(message)]
+        #[modifiers(only_owner)]
         pub fn set_status (
             &mut self,
             token_id: u64, 
@@ -202,62 +962,260 @@ pub mod my_psp34_mintable {
             health: u32,
             happy: u32
         ) -> Result<(), PSP34Error>{ 
-            self.ensure_exists_and_get_owner(Id::U64(token_id).clone())?;
-            self.asset_status.insert(&Id::U64(token_id),&Status {hungry,health,happy});
+            self.write_status(token_id, Status {hungry,health,happy})
+        }
+
+        // replaces the status of a living pet, clamped to its caps
+        fn write_status(&mut self, token_id: u64, status: Status) -> Result<(), PSP34Error> {
+            self.ensure_exists_and_get_owner(Id::U64(token_id))?;
+            self.settle(token_id);
+            self.ensure_alive(token_id)?;
+            let status = self.caps_of(token_id).clamp(status);
+            // a new baseline, so decay starts over from now
+            self.store_status(token_id, status.clone(), Self::env().block_timestamp());
+            self.update_lifecycle(token_id);
+            Self::env().emit_event(StatusChanged { token_id, status });
             Ok(())
         }
 
         #[ink(message)]
         pub fn get_status(&self, token_id: u64) -> Option<Status> {
-            self.asset_status.get(&Id::U64(token_id))
+            self.pet_status.get(&Id::U64(token_id)).map(|pet| pet.status)
         }
 
         #[ink(message)]
         pub fn get_current_status(&self, token_id: u64) -> Option<Status> {
 
-            //　get the current time
-            let current_time = Self::env().block_timestamp();
-    
-            // get the last eaten time
-            let last_checked_time = self.last_eaten.get(&Id::U64(token_id)).unwrap_or(Default::default());
-
-            if last_checked_time == 0 {
-                return Some(Status {
-                    hungry: 0,
-                    health: 0,
-                    happy: 0,
-                });
-            } else {
-            
-                let past_time = current_time - last_checked_time;
-    
-                // 60 seconds（60 ※ 1000 miliseconds）
-                let past_day = past_time / (60 * 1000) ;
-                // Assuming a hypothetical decrease of 5 per unit
-                let change_status = past_day * 5;
-    
-                let original_status = self.get_status(token_id.clone()).unwrap_or_else(|| {
-                    // In case the token_id doesn't exist in the asset_status map, we just return a default status with all fields set to 0.
-                    Status { hungry: 0, health: 0, happy: 0 }
-                });
-    
-                let new_hungy_status = original_status.hungry + (change_status as u32);
-                let new_health_status = original_status.health.saturating_sub(change_status as u32);
-                let new_happy_status = original_status.happy.saturating_sub(change_status as u32);
-    
-                return Some(Status {
-                    hungry: new_hungy_status,
-                    health: new_health_status,
-                    happy: new_happy_status,
-                });
+            let config = self.get_decay_config();
+            // decay since the status was last updated
+            let intervals = self.get_pending_intervals(token_id);
+
+            let original_status = self.get_status(token_id.clone()).unwrap_or_else(|| {
+                // In case the token_id doesn't exist in the pet_status map, we just return a default status with all fields set to 0.
+                Status { hungry: 0, health: 0, happy: 0 }
+            });
+
+            let new_hungy_status = config.hungry.apply(original_status.hungry, intervals);
+            let new_health_status = config.health.apply(original_status.health, intervals);
+            let new_happy_status = config.happy.apply(original_status.happy, intervals);
+
+            Some(self.caps_of(token_id).clamp(Status {
+                hungry: new_hungy_status,
+                health: new_health_status,
+                happy: new_happy_status,
+            }))
+        }
+
+        /// Plays with a pet, making it happier. Once a minute, like eating.
+        ///
+        /// Only the owner of the pet or an account approved for it can play with it.
+        #[ink(message)]
+        pub fn play_with_pet(&mut self, token_id: u64) -> Result<(), ContractError> {
+            let id = Id::U64(token_id);
+            self.ensure_owner_or_approved(Self::env().caller(), id.clone())?;
+            if !self.five_minutes_has_passed(self.last_played.get(&id).unwrap_or_default()) {
+                return Err(ContractError::TimeHasNotPassed)
+            }
+            self.settle(token_id);
+            self.ensure_alive(token_id)?;
+
+            let mut status = self.get_current_status(token_id).unwrap_or_default();
+            status.happy = status.happy.saturating_add(PLAY_HAPPY);
+            let status = self.caps_of(token_id).clamp(status);
+            let last_updated = self.get_last_updated(token_id).unwrap_or(Self::env().block_timestamp());
+            self.store_status(token_id, status.clone(), last_updated);
+            self.last_played.insert(&id, &Self::env().block_timestamp());
+            self.update_lifecycle(token_id);
+            Self::env().emit_event(StatusChanged { token_id, status });
+            Self::env().emit_event(PetPlayed { token_id });
+
+            let xp = self.get_xp_rewards().play + self.record_daily_care(token_id);
+            self.gain_xp(token_id, xp);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_xp(&self, token_id: u64) -> u64 {
+            self.xp.get(&Id::U64(token_id)).unwrap_or_default()
+        }
+
+        /// Returns the level of `token_id`, starting at 1.
+        #[ink(message)]
+        pub fn get_level(&self, token_id: u64) -> u32 {
+            let xp = self.get_xp(token_id);
+            let reached = self.get_level_thresholds().iter().take_while(|threshold| **threshold <= xp).count();
+            1 + reached as u32
+        }
+
+        #[ink(message)]
+        pub fn get_care_streak(&self, token_id: u64) -> Option<CareStreak> {
+            self.care_streaks.get(&Id::U64(token_id))
+        }
+
+        /// Sets the xp needed for each level from level 2 on, and how much each level raises the stat caps.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_level_config(&mut self, thresholds: Vec<u64>, cap_bonus: u32) -> Result<(), ContractError> {
+            if thresholds.len() > MAX_LEVEL_THRESHOLDS
+                || thresholds.windows(2).any(|pair| pair[0] >= pair[1])
+            {
+                return Err(ContractError::InvalidLevelThresholds)
+            }
+            self.level_thresholds.set(&thresholds);
+            self.level_cap_bonus.set(&cap_bonus);
+            Self::env().emit_event(LevelConfigChanged { thresholds, cap_bonus });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_level_thresholds(&self) -> Vec<u64> {
+            self.level_thresholds.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_level_cap_bonus(&self) -> u32 {
+            self.level_cap_bonus.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_xp_rewards(&mut self, rewards: XpRewards) -> Result<(), ContractError> {
+            self.xp_rewards.set(&rewards);
+            Self::env().emit_event(XpRewardsChanged { rewards });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_xp_rewards(&self) -> XpRewards {
+            self.xp_rewards.get().unwrap_or_default()
+        }
+
+        /// Folds the decay accrued so far into the stored status of `token_id`.
+        ///
+        /// The current status doesn't change, so no event is emitted.
+        #[ink(message)]
+        pub fn settle_status(&mut self, token_id: u64) -> Result<(), PSP34Error> {
+            self.ensure_exists_and_get_owner(Id::U64(token_id))?;
+            self.settle(token_id);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_last_updated(&self, token_id: u64) -> Option<u64> {
+            self.pet_status.get(&Id::U64(token_id)).map(|pet| pet.last_updated)
+        }
+
+        /// Sets the range each stat is kept in. Stored statuses are clamped when next read.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_stat_caps(&mut self, caps: StatCaps) -> Result<(), ContractError> {
+            if !caps.is_valid() {
+                return Err(ContractError::InvalidStatCaps)
+            }
+            self.stat_caps.set(&caps);
+            Self::env().emit_event(StatCapsChanged { caps });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_stat_caps(&self) -> StatCaps {
+            self.stat_caps.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_lifecycle_config(&mut self, config: LifecycleConfig) -> Result<(), ContractError> {
+            self.lifecycle.set(&config);
+            Self::env().emit_event(LifecycleConfigChanged { config });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_lifecycle_config(&self) -> LifecycleConfig {
+            self.lifecycle.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_pet_state(&self, token_id: u64) -> PetState {
+            if self.died_at.get(&Id::U64(token_id)).is_some() {
+                return PetState::Dead
+            }
+            let lifecycle = self.get_lifecycle_config();
+            match self.get_critical_since(token_id) {
+                Some(since) if Self::env().block_timestamp().saturating_sub(since) >= lifecycle.death_delay => {
+                    PetState::Dead
+                }
+                Some(_) => PetState::Critical,
+                None => {
+                    let status = self.get_current_status(token_id).unwrap_or_default();
+                    if status.health < lifecycle.sick_health {
+                        PetState::Sick
+                    } else {
+                        PetState::Healthy
+                    }
+                }
+            }
+        }
+
+        /// Brings a dead pet back at half health, paid by the caller in game money or apples.
+        #[ink(message)]
+        pub fn revive(&mut self, token_id: u64, payment: RevivePayment) -> Result<(), ContractError> {
+            self.ensure_exists_and_get_owner(Id::U64(token_id))?;
+            if self.get_pet_state(token_id) != PetState::Dead {
+                return Err(ContractError::PetIsNotDead)
+            }
+            let payer = Self::env().caller();
+            let lifecycle = self.get_lifecycle_config();
+            match payment {
+                RevivePayment::GameMoney => self.subtract_your_money(payer, lifecycle.revive_price)?,
+                RevivePayment::Apples => {
+                    let apples = self.get_your_apple(payer);
+                    if apples < lifecycle.revive_apples {
+                        return Err(ContractError::NotEnoughApple)
+                    }
+                    self.set_your_apple(payer, apples - lifecycle.revive_apples);
+                }
+            }
+
+            let id = Id::U64(token_id);
+            let caps = self.caps_of(token_id);
+            let status = caps.clamp(Status {
+                hungry: caps.hungry.min,
+                health: caps.health.max / 2,
+                happy: caps.happy.max / 2,
+            });
+            self.died_at.remove(&id);
+            self.critical_since.remove(&id);
+            self.store_status(token_id, status.clone(), Self::env().block_timestamp());
+            Self::env().emit_event(StatusChanged { token_id, status });
+            Self::env().emit_event(PetRevived { token_id, payer, payment });
+            Ok(())
+        }
+
+        /// Sets how statuses change over time. Applies to all time passed since each pet's last settlement.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_decay_config(&mut self, config: DecayConfig) -> Result<(), ContractError> {
+            if config.interval == 0 {
+                return Err(ContractError::InvalidDecayConfig)
             }
+            self.decay_config.set(&config);
+            Self::env().emit_event(DecayConfigChanged { config });
+            Ok(())
         }
 
         #[ink(message)]
-        pub fn change_some_status(&mut self, token_id: u64, number: u32) -> Result<(), PSP34Error> {
+        pub fn get_decay_config(&self) -> DecayConfig {
+            self.decay_config.get().unwrap_or_default()
+        }
+
+        // feeding: lowers hungry and raises health and happy by `number`
+        fn change_some_status(&mut self, token_id: u64, number: u32) -> Result<(), PSP34Error> {
             self.ensure_exists_and_get_owner(Id::U64(token_id).clone())?;
+            self.settle(token_id);
+            self.ensure_alive(token_id)?;
             let original_status = self.get_current_status(token_id.clone()).unwrap_or_else(|| {
-                // In case the token_id doesn't exist in the asset_status map, we just return a default status with all fields set to 0.
+                // In case the token_id doesn't exist in the pet_status map, we just return a default status with all fields set to 0.
                 Status { hungry: 0, health: 0, happy: 0 }
             });
     
@@ -268,20 +1226,23 @@ pub mod my_psp34_mintable {
                 hungry_status = 0;
             }
         
-            let new_status = Status {
+            let new_status = self.caps_of(token_id).clamp(Status {
                 hungry: hungry_status,
-                health: original_status.health + number,
-                happy: original_status.happy + number,
-            };
+                health: original_status.health.saturating_add(number),
+                happy: original_status.happy.saturating_add(number),
+            });
         
-            self.asset_status.insert(&Id::U64(token_id), &new_status);
+            let last_updated = self.get_last_updated(token_id).unwrap_or(Self::env().block_timestamp());
+            self.store_status(token_id, new_status.clone(), last_updated);
+            self.update_lifecycle(token_id);
+            Self::env().emit_event(StatusChanged { token_id, status: new_status });
             Ok(())
         }
 
         #[ink(message)]
         pub fn get_total_status(&self, token_id: u64) -> u32 {
             let original_status = self.get_current_status(token_id.clone()).unwrap_or_else(|| {
-                // In case the token_id doesn't exist in the asset_status map, we just return a default status with all fields set to 0.
+                // In case the token_id doesn't exist in the pet_status map, we just return a default status with all fields set to 0.
                 Status { hungry: 0, health: 0, happy: 0 }
             });
         
@@ -296,37 +1257,144 @@ pub mod my_psp34_mintable {
             result as u32
         }
 
+        /// Returns the index of the highest tier whose `min_score` the total status reaches.
+        #[ink(message)]
+        pub fn get_condition(&self , token_id: u64) -> u32 {
+            let condition = self.get_total_status(token_id);
+            let reached = self
+                .get_condition_tiers()
+                .iter()
+                .take_while(|tier| tier.min_score <= condition)
+                .count();
+            reached.saturating_sub(1) as u32
+        }
+
+        #[ink(message)]
+        pub fn get_condition_label(&self, token_id: u64) -> String {
+            self.get_condition_tier(token_id).label
+        }
+
+        /// Sets the collection name and symbol shown by marketplaces.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_collection_metadata(&mut self, name: String, symbol: String) -> Result<(), PSP34Error> {
+            let collection_id = self.collection_id();
+            self._set_attribute(collection_id.clone(), String::from("name"), name);
+            self._set_attribute(collection_id, String::from("symbol"), symbol);
+            Ok(())
+        }
+
+        /// Stores a fixed attribute of a token. The live status attributes can't be overwritten.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_token_attribute(&mut self, token_id: u64, key: String, value: String) -> Result<(), ContractError> {
+            self.ensure_exists_and_get_owner(Id::U64(token_id))?;
+            if LIVE_ATTRIBUTES.contains(&key.as_slice()) {
+                return Err(ContractError::ReadOnlyAttribute)
+            }
+            self._set_attribute(Id::U64(token_id), key, value);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_condition_url(&self , token_id: u64) -> String {
+            let condition = self.get_condition(token_id);
+            self.stage_uris
+                .get(&(self.get_stage(token_id), condition))
+                .unwrap_or_else(|| self.get_condition_tier(token_id).base_uri)
+        }
+
+        #[ink(message)]
+        pub fn get_stage(&self, token_id: u64) -> Stage {
+            self.stages.get(&Id::U64(token_id)).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_born_at(&self, token_id: u64) -> Option<u64> {
+            self.born_at.get(&Id::U64(token_id))
+        }
+
+        /// Moves a pet to its next stage once it is old, experienced and well enough, for a game money price.
+        ///
+        /// Only the owner of the pet or an account approved for it can evolve it; the caller pays.
+        #[ink(message)]
+        pub fn evolve(&mut self, token_id: u64) -> Result<(), ContractError> {
+            let id = Id::U64(token_id);
+            self.ensure_owner_or_approved(Self::env().caller(), id.clone())?;
+            self.settle(token_id);
+            self.ensure_alive(token_id)?;
+
+            let stage = self.get_stage(token_id);
+            let next = stage.next().ok_or(ContractError::MaxStageReached)?;
+            let requirement = *self
+                .get_evolution_requirements()
+                .get(stage as usize)
+                .ok_or(ContractError::MaxStageReached)?;
+            if self.get_level(token_id) < requirement.min_level {
+                return Err(ContractError::LevelTooLow)
+            }
+            if self.get_condition(token_id) < requirement.min_condition {
+                return Err(ContractError::ConditionTooLow)
+            }
+            // pets minted before birth times were kept count as old enough
+            let born_at = self.get_born_at(token_id).unwrap_or_default();
+            if Self::env().block_timestamp().saturating_sub(born_at) < requirement.min_age {
+                return Err(ContractError::TimeHasNotPassed)
+            }
+            self.subtract_your_money(Self::env().caller(), requirement.price)?;
+
+            self.stages.insert(&id, &next);
+            Self::env().emit_event(PetEvolved { token_id, stage: next });
+            Ok(())
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_evolution_requirements(&mut self, requirements: Vec<EvolutionRequirement>) -> Result<(), ContractError> {
+            if requirements.len() != 3 {
+                return Err(ContractError::InvalidEvolutionRequirements)
+            }
+            self.evolution_requirements.set(&requirements);
+            Ok(())
+        }
+
         #[ink(message)]
-        pub fn get_condition(&self , token_id: u64) -> u32 {
-            let condition = self.get_total_status(token_id);
-            // bad condition
-            if condition < 100 {
-                0
-            } 
-            // normal condition
-            else if condition < 200 {
-                1
-            } 
-            // good condition
-            else {
-                2
-            }
+        pub fn get_evolution_requirements(&self) -> Vec<EvolutionRequirement> {
+            self.evolution_requirements.get().unwrap_or_default()
         }
 
+        /// Sets the base uri of pets in `stage` and condition tier `condition`. An empty uri removes it.
         #[ink(message)]
-        pub fn get_condition_url(&self , token_id: u64) -> String {
-            let condition = self.get_condition(token_id);
-            if condition == 0 {
-                self.get_bad_uri()
-            } else if condition == 1 {
-                self.get_normal_uri()
+        #[modifiers(only_owner)]
+        pub fn set_stage_uri(&mut self, stage: Stage, condition: u32, uri: String) -> Result<(), PSP34Error> {
+            if uri.is_empty() {
+                self.stage_uris.remove(&(stage, condition));
             } else {
-                self.get_good_uri()
+                self.stage_uris.insert(&(stage, condition), &uri);
             }
+            Ok(())
         }
 
+        #[ink(message)]
+        pub fn get_stage_uri(&self, stage: Stage, condition: u32) -> Option<String> {
+            self.stage_uris.get(&(stage, condition))
+        }
+
+        /// Feeds one of the caller's apples to a pet the caller owns or is approved for.
+        ///
+        /// `account_id` has to be the caller.
         #[ink(message)]
         pub fn eat_an_apple(&mut self, token_id: u64, account_id: AccountId) -> Result<(),ContractError> {
+            let caller = Self::env().caller();
+            if caller != account_id {
+                return Err(ContractError::InvalidAccountId)
+            }
+            self.ensure_owner_or_approved(caller, Id::U64(token_id))?;
+
+            self.settle(token_id);
+            if self.get_pet_state(token_id) == PetState::Dead {
+                return Err(ContractError::PetIsDead)
+            }
 
             // get last eaten time
             let last_eaten = self.get_last_eaten(Id::U64(token_id).clone());
@@ -345,23 +1413,130 @@ pub mod my_psp34_mintable {
 
                 // branching by pseudo random
                 let random = self.get_pseudo_random(100);
-                if random < 25 {
+                let outcome = if random < 25 {
                     self.change_some_status(token_id, 30)?;
-                    Ok(())
+                    EatOutcome::Normal
                 } else if random < 50 {
                     self.set_full_status(token_id)?;
-                    Ok(())
+                    EatOutcome::Full
                 } else if random < 75 {
                     self.set_lucky_status(token_id)?;
-                    Ok(())
+                    EatOutcome::Lucky
                 } else {
                     self.set_death_status(token_id)?;
-                    Ok(())
-                } 
+                    EatOutcome::Death
+                };
+                Self::env().emit_event(AppleEaten { account: account_id, token_id, outcome });
+                let xp = self.get_xp_rewards().eat + self.record_daily_care(token_id);
+                self.gain_xp(token_id, xp);
+                Ok(())
+            }
+        }
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_metadata_mode(&mut self, mode: MetadataMode) -> Result<(), PSP34Error> {
+            self.metadata_mode.set(&mode);
+            Self::env().emit_event(MetadataModeChanged { mode });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_metadata_mode(&self) -> MetadataMode {
+            self.metadata_mode.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_description(&mut self, description: String) -> Result<(), PSP34Error> {
+            self.description.set(&description);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_description(&self) -> String {
+            self.description.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_svg_config(&mut self, svg_config: SvgConfig) -> Result<(), PSP34Error> {
+            self.svg_config.set(&svg_config);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_svg_config(&self) -> SvgConfig {
+            self.svg_config.get().unwrap_or_default()
+        }
+
+        /// Draws `token_id` from its condition and current status.
+        #[ink(message)]
+        pub fn get_svg(&self, token_id: u64) -> String {
+            let config = &self.get_svg_config();
+            let status = self.get_current_status(token_id).unwrap_or_default();
+            let condition = self.get_condition(token_id) as usize;
+            let background = config
+                .backgrounds
+                .get(condition)
+                .or(config.backgrounds.last())
+                .cloned()
+                .unwrap_or_default();
+            // frowning in the lowest tier, smiling in the highest
+            let mouth = if condition == 0 {
+                "M86 100 Q100 88 114 100"
+            } else if condition + 1 < self.get_condition_tiers().len() {
+                "M86 96 L114 96"
+            } else {
+                "M86 92 Q100 104 114 92"
+            };
+
+            let mut svg = String::from("<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 200 200\">");
+            svg.extend_from_slice(format!(
+                "<rect width=\"200\" height=\"200\" fill=\"{}\"/>\
+                <circle cx=\"100\" cy=\"80\" r=\"40\" fill=\"{}\"/>\
+                <circle cx=\"86\" cy=\"72\" r=\"4\"/><circle cx=\"114\" cy=\"72\" r=\"4\"/>\
+                <path d=\"{}\" stroke=\"#000\" stroke-width=\"3\" fill=\"none\"/>",
+                as_str(&background), as_str(&config.body), mouth
+            ).as_bytes());
+            for layer in config.layers.iter() {
+                svg.extend_from_slice(layer);
             }
+            // bars are 100 wide when full
+            let bars = [
+                (status.hungry, &config.hungry_bar),
+                (status.health, &config.health_bar),
+                (status.happy, &config.happy_bar),
+            ];
+            for (i, (value, color)) in bars.iter().enumerate() {
+                svg.extend_from_slice(format!(
+                    "<rect x=\"50\" y=\"{}\" width=\"{}\" height=\"8\" fill=\"{}\"/>",
+                    140 + 15 * i, (*value).min(100), as_str(color)
+                ).as_bytes());
+            }
+            svg.extend_from_slice(format!(
+                "<text x=\"100\" y=\"195\" font-size=\"12\" text-anchor=\"middle\">#{}</text></svg>",
+                token_id
+            ).as_bytes());
+            svg
+        }
+
+        #[ink(message)]
+        pub fn get_image_uri(&self, token_id: u64) -> String {
+            let mut uri = String::from("data:image/svg+xml;base64,");
+            uri.extend_from_slice(&base64_encode(&self.get_svg(token_id)));
+            uri
         }
+
+        /// Returns the metadata uri of `token_id`: the condition uri followed by the token id,
+        /// or a `data:application/json;base64,` uri in on-chain mode.
         #[ink(message)]
         pub fn token_uri(&self , token_id: u64) -> String {
+            if self.get_metadata_mode() == MetadataMode::OnChain {
+                let mut uri = String::from("data:application/json;base64,");
+                uri.extend_from_slice(&base64_encode(&self.get_token_json(token_id)));
+                return uri
+            }
+
             let id_string:ink::prelude::string::String = match Id::U64(token_id).clone() {
                 Id::U8(u8) => {
                     let tmp: u8 = u8;
@@ -430,6 +1605,7 @@ pub mod my_psp34_mintable {
 
                 // set last_staked
                 self.last_staked.insert(&account_id, &current_time);
+                Self::env().emit_event(MoneyStaked { account: account_id, amount: stake_money });
                 Ok(())
             }
         }
@@ -483,6 +1659,7 @@ pub mod my_psp34_mintable {
                 self
                     .your_money
                     .insert(&account_id, &result_money);
+                Self::env().emit_event(MoneyWithdrawn { account: account_id, amount: staked_money });
                 Ok(())
             }
         }
@@ -495,6 +1672,7 @@ pub mod my_psp34_mintable {
             // add 1
             let after_apple = self.get_your_apple(account_id) + 1;
             self.apple_number.insert(&account_id, &after_apple);
+            Self::env().emit_event(ApplePurchased { account: account_id, price: 20, apples: after_apple });
             Ok(())
         }
 
@@ -527,53 +1705,617 @@ pub mod my_psp34_mintable {
 
             let after_money = self.get_your_money(account_id) + 100;
             self.set_your_money(account_id, after_money);
+            Self::env().emit_event(DailyBonusClaimed { account: account_id, amount: 100 });
+
+            Ok(())
+            }
+        }
+
+        // moves PSP22 of the caller through this contract's trusted spender budget, so only
+        // game payments may use it
+        fn call_psp22_transfer(&mut self, target_account_id:AccountId, to: AccountId, value: Balance, data: String)  -> Result<(), PSP22Error> {
+            let mut interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(target_account_id);
+            let from = Self::env().caller();
+            interface.transfer_from_contract(from, to, value, data)?;
+            Ok(())
+        }
+
+        // burns PSP22 of the caller through this contract's trusted spender budget, like
+        // `call_psp22_transfer`
+        fn call_psp22_burn(&mut self, target_account_id:AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let mut interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(target_account_id);
+            let from = Self::env().caller();
+            interface.burn_from_contract(from, value)?;
+            Ok(())
+        }
+
+        /// Pays 500 PSP22 from the caller and credits 300 game money.
+        ///
+        /// `target_account_id` has to be the payment token once one is set. `burn_rate` of the
+        /// payment is burned and the rest goes to `to`. This contract must be a trusted spender on
+        /// the PSP22 contract and the caller must have opted in to it there with `set_spender_opt_in`.
+        #[ink(message)]
+        pub fn buy_game_money(&mut self, target_account_id:AccountId, to: AccountId, data: String) -> Result<(), ContractError>{
+            self.ensure_payment_token(target_account_id)?;
+            let interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(target_account_id);
+            let from = Self::env().caller();
+            let money = interface.balance_of(from);
+            if money < 500 {
+                Err(ContractError::NotEnoughMoney.into())
+            } else {
+                let burn_amount = self.pay_psp22(target_account_id, to, 500, data)?;
+                self.plus_your_money(from, 300);
+                Self::env().emit_event(GameMoneyBought {
+                    buyer: from,
+                    to,
+                    paid: 500,
+                    burned: burn_amount,
+                    game_money: 300,
+                });
+                Ok(())
+            }
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_burn_rate(&mut self, burn_rate: u16) -> Result<(), ContractError> {
+            if burn_rate > 10000 {
+                return Err(ContractError::InvalidBurnRate)
+            }
+            self.burn_rate.set(&burn_rate);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_burn_rate(&self) -> u16 {
+            self.burn_rate.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_total_burned(&self) -> Balance {
+            self.total_burned.get().unwrap_or_default()
+        }
+
+        /// Sets the PSP22 contract `buy_game_money` and `breed` take payments in.
+        ///
+        /// Until it is set, they take payments in whichever contract the caller names.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_payment_token(&mut self, token: AccountId) -> Result<(), ContractError> {
+            self.payment_token.set(&token);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_payment_token(&self) -> Option<AccountId> {
+            self.payment_token.get()
+        }
+
+        /// Replaces the contract code, keeping all storage. Call `migrate` afterwards.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_code(&mut self, code_hash: [u8; 32]) -> Result<(), ContractError> {
+            ink::env::set_code_hash(&code_hash).map_err(|_| ContractError::UpgradeFailed)?;
+            Ok(())
+        }
+
+        /// Brings stored data up to `STORAGE_VERSION`, migrating at most `max_tokens` tokens per call.
+        ///
+        /// Returns `true` once everything is migrated; call it again while it returns `false`.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn migrate(&mut self, max_tokens: u64) -> Result<bool, ContractError> {
+            if max_tokens == 0 {
+                return Err(ContractError::InvalidMigrationBatch)
+            }
+            let mut migrated: u64 = 0;
+            while self.get_storage_version() < STORAGE_VERSION {
+                let from_version = self.get_storage_version();
+                let mut cursor = self.migration_cursor.get().unwrap_or_default();
+                // tokens are never burned, so the enumeration only grows and every minted id,
+                // whatever its value, is visited once
+                while (cursor as Balance) < self.total_supply() {
+                    if migrated == max_tokens {
+                        self.migration_cursor.set(&cursor);
+                        return Ok(false)
+                    }
+                    let token_id = self.token_by_index(cursor as u128)?;
+                    self.migrate_token(from_version, token_id)?;
+                    cursor += 1;
+                    migrated += 1;
+                }
+                self.migrate_contract(from_version)?;
+                self.storage_version.set(&(from_version + 1));
+                self.migration_cursor.set(&0);
+            }
+            Ok(true)
+        }
+
+        #[ink(message)]
+        pub fn get_storage_version(&self) -> u32 {
+            self.storage_version.get().unwrap_or_default()
+        }
+
+        /// Mints a pet to the caller from two parents the caller owns or is approved for.
+        ///
+        /// The child starts around the average status of its parents. Each parent then has to wait
+        /// `cooldown` before breeding again, and can breed at most `max_breeds` times.
+        #[ink(message)]
+        pub fn breed(&mut self, parent_a: u64, parent_b: u64, payment: BreedPayment) -> Result<u64, ContractError> {
+            let caller = Self::env().caller();
+            if parent_a == parent_b {
+                return Err(ContractError::InvalidParents)
+            }
+            for parent in [parent_a, parent_b] {
+                self.ensure_can_breed(caller, parent)?;
+            }
+
+            let config = self.get_breed_config();
+            match payment {
+                BreedPayment::GameMoney => self.subtract_your_money(caller, config.price)?,
+                BreedPayment::Psp22(target_account_id) => {
+                    let owner = self.owner();
+                    self.pay_psp22(target_account_id, owner, config.psp22_price, String::new())?;
+                }
+            }
+
+            let child = self.current_token_id.checked_add(1).ok_or(ContractError::NumberOverflowError)?;
+            self.mint(caller, Id::U64(child))?;
+            self.current_token_id = child;
+
+            let status_a = self.get_current_status(parent_a).unwrap_or_default();
+            let status_b = self.get_current_status(parent_b).unwrap_or_default();
+            let hungry = self.inherit_stat(status_a.hungry, status_b.hungry);
+            let health = self.inherit_stat(status_a.health, status_b.health);
+            let happy = self.inherit_stat(status_a.happy, status_b.happy);
+            self.write_status(child, Status { hungry, health, happy })?;
+
+            let generation = [parent_a, parent_b]
+                .iter()
+                .map(|parent| self.get_lineage(*parent).map_or(0, |lineage| lineage.generation))
+                .max()
+                .unwrap_or_default();
+            self.lineage.insert(&Id::U64(child), &Lineage { parent_a, parent_b, generation: generation + 1 });
+
+            let now = Self::env().block_timestamp();
+            for parent in [parent_a, parent_b] {
+                let id = Id::U64(parent);
+                let count = self.get_breed_count(parent) + 1;
+                self.last_bred.insert(&id, &now);
+                self.breed_count.insert(&id, &count);
+            }
+            Self::env().emit_event(PetBred { owner: caller, parent_a, parent_b, child });
+            Ok(child)
+        }
+
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn set_breed_config(&mut self, config: BreedConfig) -> Result<(), ContractError> {
+            self.breed_config.set(&config);
+            Self::env().emit_event(BreedConfigChanged { config });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_breed_config(&self) -> BreedConfig {
+            self.breed_config.get().unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_breed_count(&self, token_id: u64) -> u32 {
+            self.breed_count.get(&Id::U64(token_id)).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_last_bred(&self, token_id: u64) -> Option<u64> {
+            self.last_bred.get(&Id::U64(token_id))
+        }
+
+        #[ink(message)]
+        pub fn get_lineage(&self, token_id: u64) -> Option<Lineage> {
+            self.lineage.get(&Id::U64(token_id))
+        }
+
+        /// The two pets `token_id` was bred from; `None` for claimed pets.
+        #[ink(message)]
+        pub fn get_parents(&self, token_id: u64) -> Option<(u64, u64)> {
+            self.get_lineage(token_id).map(|lineage| (lineage.parent_a, lineage.parent_b))
+        }
+
+        #[ink(message)]
+        pub fn claim_a_nft(&mut self) -> Result<(), ContractError> {
+
+            let to = Self::env().caller();
+
+            let token_id = self.current_token_id.checked_add(1).ok_or(ContractError::NumberOverflowError)?;
+
+            let nft_balance = self.balance_of(to.clone());
+
+            if nft_balance > 0 {
+                Err(ContractError::AlreadyHadOneNft.into())
+            } else {
+                self.mint(to,Id::U64(token_id))?;
+                self.current_token_id = token_id;
+                Self::env().emit_event(NftClaimed { owner: to, token_id });
+                Ok(())
+            }
+            
+        }
+
+        // internal function
+
+        // the current value of one of the `LIVE_ATTRIBUTES`
+        pub fn get_live_attribute(&self, token_id: u64, key: &[u8]) -> Option<String> {
+            self.owner_of(Id::U64(token_id))?;
+            let status = self.get_current_status(token_id)?;
+            let value = match key {
+                b"hungry" => status.hungry.to_string().into_bytes(),
+                b"health" => status.health.to_string().into_bytes(),
+                b"happy" => status.happy.to_string().into_bytes(),
+                b"condition" => self.get_condition_label(token_id),
+                b"level" => self.get_level(token_id).to_string().into_bytes(),
+                _ => return None,
+            };
+            Some(value)
+        }
+
+        // the on-chain metadata document of a token
+        pub fn get_token_json(&self, token_id: u64) -> String {
+            let status = self.get_current_status(token_id).unwrap_or_default();
+            let mut name = self
+                .get_attribute(self.collection_id(), String::from("name"))
+                .unwrap_or_else(|| String::from("Pet"));
+            name.extend_from_slice(format!(" #{}", token_id).as_bytes());
+            let image = self.get_image_uri(token_id);
+
+            let mut json = String::from("{\"name\":");
+            push_json_string(&mut json, &name);
+            json.extend_from_slice(b",\"description\":");
+            push_json_string(&mut json, &self.get_description());
+            json.extend_from_slice(b",\"image\":");
+            push_json_string(&mut json, &image);
+            json.extend_from_slice(format!(
+                ",\"attributes\":[\
+                {{\"trait_type\":\"hungry\",\"value\":{}}},\
+                {{\"trait_type\":\"health\",\"value\":{}}},\
+                {{\"trait_type\":\"happy\",\"value\":{}}},\
+                {{\"trait_type\":\"condition\",\"value\":",
+                status.hungry, status.health, status.happy
+            ).as_bytes());
+            push_json_string(&mut json, &self.get_condition_label(token_id));
+            // marketplaces expect dates in seconds
+            json.extend_from_slice(format!(
+                "}},{{\"trait_type\":\"level\",\"value\":{}}},\
+                {{\"trait_type\":\"last_eaten\",\"display_type\":\"date\",\"value\":{}}}]}}",
+                self.get_level(token_id),
+                self.get_last_eaten(Id::U64(token_id)) / 1000
+            ).as_bytes());
+            json
+        }
+
+        // whole decay intervals passed since the status of `token_id` was last updated
+        pub fn get_pending_intervals(&self, token_id: u64) -> u64 {
+            match self.get_last_updated(token_id) {
+                Some(last_updated) => {
+                    Self::env().block_timestamp().saturating_sub(last_updated) / self.get_decay_config().interval
+                }
+                None => 0,
+            }
+        }
+
+        // stores the current status and moves `last_updated` forward by the whole intervals applied,
+        // so the remainder still counts towards the next interval
+        pub fn settle(&mut self, token_id: u64) {
+            // before the baseline moves, so the time the pet became critical is kept
+            self.update_lifecycle(token_id);
+            let intervals = self.get_pending_intervals(token_id);
+            if intervals == 0 {
+                return
+            }
+            let status = self.get_current_status(token_id).unwrap_or_default();
+            let last_updated = self.get_last_updated(token_id).unwrap_or_default();
+            self.store_status(token_id, status, last_updated + intervals * self.get_decay_config().interval);
+        }
+
+        pub fn store_status(&mut self, token_id: u64, status: Status, last_updated: u64) {
+            self.pet_status.insert(&Id::U64(token_id), &PetStatus { status, last_updated });
+        }
+
+        // the caps that apply to `token_id`
+        pub fn caps_of(&self, token_id: u64) -> StatCaps {
+            let mut caps = self.get_stat_caps();
+            let bonus = self.get_level_cap_bonus().saturating_mul(self.get_level(token_id) - 1);
+            caps.health.max = caps.health.max.saturating_add(bonus);
+            caps.happy.max = caps.happy.max.saturating_add(bonus);
+            caps
+        }
+
+        // pays `amount` PSP22 from the caller, burning `burn_rate` of it and sending the rest to `to`
+        pub fn pay_psp22(
+            &mut self,
+            target_account_id: AccountId,
+            to: AccountId,
+            amount: Balance,
+            data: String,
+        ) -> Result<Balance, ContractError> {
+            self.ensure_payment_token(target_account_id)?;
+            let burn_amount = self.get_burn_amount(amount);
+            if burn_amount > 0 {
+                self.call_psp22_burn(target_account_id, burn_amount)?;
+                self.total_burned.set(&self.get_total_burned().saturating_add(burn_amount));
+            }
+            if burn_amount < amount {
+                self.call_psp22_transfer(target_account_id, to, amount - burn_amount, data)?;
+            }
+            Ok(burn_amount)
+        }
+
+        // once a payment token is set, any other contract could fake a payment; until then
+        // payments work as they did before there was one
+        pub fn ensure_payment_token(&self, token: AccountId) -> Result<(), ContractError> {
+            match self.get_payment_token() {
+                Some(payment_token) if payment_token != token => Err(ContractError::UnknownPaymentToken),
+                _ => Ok(()),
+            }
+        }
+
+        pub fn ensure_can_breed(&mut self, caller: AccountId, token_id: u64) -> Result<(), ContractError> {
+            let id = Id::U64(token_id);
+            self.ensure_owner_or_approved(caller, id.clone())?;
+            self.settle(token_id);
+            self.ensure_alive(token_id)?;
+            let config = self.get_breed_config();
+            if self.get_breed_count(token_id) >= config.max_breeds {
+                return Err(ContractError::MaxBreedsReached)
+            }
+            if let Some(last_bred) = self.last_bred.get(&id) {
+                if Self::env().block_timestamp().saturating_sub(last_bred) < config.cooldown {
+                    return Err(ContractError::BreedingCooldown)
+                }
+            }
+            Ok(())
+        }
+
+        // the average of both parents, up to 10 points either way
+        pub fn inherit_stat(&mut self, a: u32, b: u32) -> u32 {
+            let average = ((a as u64 + b as u64) / 2) as u32;
+            let variation = self.get_pseudo_random(20) as u32;
+            average.saturating_add(variation).saturating_sub(10)
+        }
+
+        // an hour to hatch, a day and level 2 to grow up, a week and level 4 to become an elder
+        pub fn default_evolution_requirements() -> Vec<EvolutionRequirement> {
+            vec![
+                EvolutionRequirement { min_level: 1, min_condition: 0, min_age: 60 * 60 * 1000, price: 50 },
+                EvolutionRequirement { min_level: 2, min_condition: 1, min_age: ONE_DAY, price: 100 },
+                EvolutionRequirement { min_level: 4, min_condition: 1, min_age: 7 * ONE_DAY, price: 300 },
+            ]
+        }
+
+        // 5 levels at 100, 300, 600 and 1000 xp, each raising the caps by 10
+        pub fn set_default_levels(&mut self) {
+            self.level_thresholds.set(&vec![100, 300, 600, 1000]);
+            self.level_cap_bonus.set(&10);
+        }
+
+        // extends the care streak on the first care of a day and returns the bonus xp for it
+        pub fn record_daily_care(&mut self, token_id: u64) -> u64 {
+            let id = Id::U64(token_id);
+            let today = Self::env().block_timestamp() / ONE_DAY;
+            let streak = match self.care_streaks.get(&id) {
+                Some(care) if care.day == today => return 0,
+                Some(care) if care.day + 1 == today => care.streak.saturating_add(1),
+                _ => 1,
+            };
+            self.care_streaks.insert(&id, &CareStreak { day: today, streak });
+            let rewards = self.get_xp_rewards();
+            rewards.daily_care.saturating_mul(streak.min(rewards.max_streak) as u64)
+        }
+
+        pub fn gain_xp(&mut self, token_id: u64, amount: u64) {
+            if amount == 0 {
+                return
+            }
+            let level = self.get_level(token_id);
+            let xp = self.get_xp(token_id).saturating_add(amount);
+            self.xp.insert(&Id::U64(token_id), &xp);
+            Self::env().emit_event(XpGained { token_id, amount, xp });
+            let new_level = self.get_level(token_id);
+            if new_level > level {
+                Self::env().emit_event(LevelUp { token_id, level: new_level });
+            }
+        }
+
+        // when the pet became critical, if it is critical now
+        pub fn get_critical_since(&self, token_id: u64) -> Option<u64> {
+            let id = Id::U64(token_id);
+            let last_updated = self.get_last_updated(token_id)?;
+            let status = self.get_current_status(token_id)?;
+            if !self.caps_of(token_id).is_critical(&status) {
+                return None
+            }
+            self.critical_since
+                .get(&id)
+                .or_else(|| self.get_critical_time(token_id, last_updated))
+                .or(Some(last_updated))
+        }
+
+        // when the stored status, taken at `from`, decays into a critical one
+        pub fn get_critical_time(&self, token_id: u64, from: u64) -> Option<u64> {
+            let caps = self.caps_of(token_id);
+            let status = caps.clamp(self.get_status(token_id).unwrap_or_default());
+            let config = self.get_decay_config();
+            let health = config.health.intervals_until(status.health, caps.health.min);
+            let hungry = config.hungry.intervals_until(status.hungry, caps.hungry.max);
+            let intervals = match (health, hungry) {
+                (Some(health), Some(hungry)) => health.min(hungry),
+                (health, hungry) => health.or(hungry)?,
+            };
+            Some(from.saturating_add(intervals.saturating_mul(config.interval)))
+        }
+
+        // records when the pet became critical, and its death once it stayed critical too long
+        pub fn update_lifecycle(&mut self, token_id: u64) {
+            let id = Id::U64(token_id);
+            if self.died_at.get(&id).is_some() {
+                return
+            }
+            let death_delay = self.get_lifecycle_config().death_delay;
+            match self.get_critical_since(token_id) {
+                Some(since) if Self::env().block_timestamp().saturating_sub(since) >= death_delay => {
+                    let time = since.saturating_add(death_delay);
+                    self.critical_since.remove(&id);
+                    self.died_at.insert(&id, &time);
+                    Self::env().emit_event(PetDied { token_id, time });
+                }
+                Some(since) => {
+                    self.critical_since.insert(&id, &since);
+                }
+                None => {
+                    self.critical_since.remove(&id);
+                }
+            }
+        }
+
+        pub fn ensure_owner_or_approved(&self, caller: AccountId, id: Id) -> Result<(), ContractError> {
+            let owner = self.ensure_exists_and_get_owner(id.clone())?;
+            if owner != caller && !self.allowance(owner, caller, Some(id)) {
+                return Err(ContractError::NotApproved)
+            }
+            Ok(())
+        }
 
+        pub fn ensure_alive(&self, token_id: u64) -> Result<(), PSP34Error> {
+            if self.get_pet_state(token_id) == PetState::Dead {
+                return Err(PSP34Error::Custom(String::from("PetIsDead")))
+            }
             Ok(())
+        }
+
+        // moves one token's data from the `from_version` layout to the next one
+        pub fn migrate_token(&mut self, from_version: u32, token_id: Id) -> Result<(), ContractError> {
+            match from_version {
+                // version 2 stored bare statuses, decayed from the last meal
+                2 => {
+                    if let Some(status) = self.asset_status.get(&token_id) {
+                        let last_eaten = self.last_eaten.get(&token_id).unwrap_or_default();
+                        let last_updated = if last_eaten == 0 { Self::env().block_timestamp() } else { last_eaten };
+                        self.pet_status.insert(&token_id, &PetStatus { status, last_updated });
+                        self.asset_status.remove(&token_id);
+                    }
+                    Ok(())
+                }
+                // version 6 had no lifecycle; a pet already critical gets the whole death delay
+                // from now instead of having died while nobody could tell
+                6 => {
+                    if let Id::U64(pet) = &token_id {
+                        if self.get_critical_since(*pet).is_some() {
+                            self.critical_since.insert(&token_id, &Self::env().block_timestamp());
+                        }
+                    }
+                    Ok(())
+                }
+                // the other versions only changed contract-wide data
+                0 | 1 | 3 | 4 | 5 | 7 | 8 | 9 => Ok(()),
+                _ => Err(ContractError::UnknownStorageVersion),
+            }
+        }
+
+        // moves contract-wide data to the next layout once all tokens are migrated
+        pub fn migrate_contract(&mut self, from_version: u32) -> Result<(), ContractError> {
+            match from_version {
+                // the first release, which had no version; the fields version 1 added start out
+                // empty, which is what they default to
+                0 => Ok(()),
+                // the three condition uris become the default tiers
+                1 => {
+                    let mut tiers = Self::default_condition_tiers();
+                    tiers[0].base_uri = core::mem::take(&mut self.bad_uri);
+                    tiers[1].base_uri = core::mem::take(&mut self.normal_uri);
+                    tiers[2].base_uri = core::mem::take(&mut self.good_uri);
+                    self.condition_tiers.set(&tiers);
+                    Ok(())
+                }
+                2 => Ok(()),
+                // metadata stays on IPFS, without a description
+                3 => {
+                    self.metadata_mode.set(&MetadataMode::Ipfs);
+                    self.description.set(&String::new());
+                    Ok(())
+                }
+                // the default colors, without extra layers
+                4 => {
+                    self.svg_config.set(&SvgConfig::default());
+                    Ok(())
+                }
+                // the decay the earlier versions had built in
+                5 => {
+                    self.decay_config.set(&DecayConfig::default());
+                    Ok(())
+                }
+                // stats stay within 0..=100 and pets get the default lifecycle
+                6 => {
+                    self.stat_caps.set(&StatCaps::default());
+                    self.lifecycle.set(&LifecycleConfig::default());
+                    Ok(())
+                }
+                // the default levels and xp rewards; nothing had xp yet
+                7 => {
+                    self.set_default_levels();
+                    self.xp_rewards.set(&XpRewards::default());
+                    Ok(())
+                }
+                // the default evolution requirements; every pet is still an egg
+                8 => {
+                    self.evolution_requirements.set(&Self::default_evolution_requirements());
+                    Ok(())
+                }
+                // the default breed config; payments keep working until the owner sets a payment token
+                9 => {
+                    self.breed_config.set(&BreedConfig::default());
+                    Ok(())
+                }
+                _ => Err(ContractError::UnknownStorageVersion),
             }
         }
 
-        #[ink(message)]
-        pub fn call_psp22_transfer(&mut self, target_account_id:AccountId, to: AccountId, value: Balance, data: String)  -> Result<(), PSP22Error> {
-            let mut interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(target_account_id);
-            let from = Self::env().caller();
-            interface.transfer_from_contract(from, to, value, data)?;
-            Ok(())
+        // bad below 100, normal below 200, good from 200
+        pub fn default_condition_tiers() -> Vec<ConditionTier> {
+            [(0, "bad"), (100, "normal"), (200, "good")]
+                .iter()
+                .map(|(min_score, label)| ConditionTier {
+                    min_score: *min_score,
+                    label: String::from(*label),
+                    base_uri: String::new(),
+                })
+                .collect()
         }
 
-        #[ink(message)]
-        pub fn buy_game_money(&mut self, target_account_id:AccountId, to: AccountId, data: String) -> Result<(), ContractError>{
-            let interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(target_account_id);
-            let from = Self::env().caller();
-            let money = interface.balance_of(from);
-            if money < 500 {
-                Err(ContractError::NotEnoughMoney.into())
-            } else {
-                self.call_psp22_transfer(target_account_id, to, 500, data)?;
-                self.plus_your_money(from, 300);
-                Ok(())
-            }
+        pub fn get_condition_tier(&self, token_id: u64) -> ConditionTier {
+            self.get_condition_tiers()
+                .get(self.get_condition(token_id) as usize)
+                .cloned()
+                .unwrap_or_default()
         }
 
-        #[ink(message)]
-        pub fn claim_a_nft(&mut self) -> Result<(), ContractError> {
-
-            let to = Self::env().caller();
-
-            let token_id = self.current_token_id.checked_add(1).ok_or(ContractError::NumberOverflowError)?;
-
-            let nft_balance = self.balance_of(to.clone());
-
-            if nft_balance > 0 {
-                Err(ContractError::AlreadyHadOneNft.into())
-            } else {
-                self.mint(to,Id::U64(token_id))?;
-                self.current_token_id = token_id;
-                Ok(())
-            }
-            
+        pub fn get_tier_uri(&self, label: &[u8]) -> String {
+            self.get_condition_tiers()
+                .iter()
+                .find(|tier| tier.label == label)
+                .map(|tier| tier.base_uri.clone())
+                .unwrap_or_default()
         }
 
-        // internal function
+        pub fn set_tier_uri(&mut self, label: &[u8], uri: String) -> Result<(), PSP34Error> {
+            let mut tiers = self.get_condition_tiers();
+            let tier = tiers
+                .iter_mut()
+                .find(|tier| tier.label == label)
+                .ok_or_else(|| PSP34Error::Custom(String::from("ConditionTierNotFound")))?;
+            tier.base_uri = uri;
+            self.condition_tiers.set(&tiers);
+            Ok(())
+        }
 
         pub fn is_account_id(&self, account_id: AccountId) -> bool {
             let caller = Self::env().caller();
@@ -642,6 +2384,12 @@ pub mod my_psp34_mintable {
             }
         }
 
+        // the part of a PSP22 payment that is burned; split so that large payments can't overflow
+        pub fn get_burn_amount(&self, payment: Balance) -> Balance {
+            let rate = self.get_burn_rate() as Balance;
+            payment / 10000 * rate + payment % 10000 * rate / 10000
+        }
+
         pub fn set_your_money(&mut self, account_id: AccountId, after_money: u64)  {
             self.your_money.insert(&account_id, &after_money);
         }
@@ -656,12 +2404,12 @@ pub mod my_psp34_mintable {
         }
 
         pub fn set_full_status(&mut self, token_id: u64) -> Result<(), PSP34Error> {
-            self.set_status(token_id, 0, 100, 100)?;
+            self.write_status(token_id, Status { hungry: 0, health: 100, happy: 100 })?;
             Ok(())
         }
 
         pub fn set_death_status(&mut self, token_id: u64) -> Result<(), PSP34Error> {
-            self.set_status(token_id, 80, 0, 0)?;
+            self.write_status(token_id, Status { hungry: 80, health: 0, happy: 0 })?;
             Ok(())
         }
 
@@ -706,6 +2454,44 @@ pub mod my_psp34_mintable {
     
     }
 
+    const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    // standard padded base64
+    pub fn base64_encode(input: &[u8]) -> String {
+        let mut output = String::with_capacity((input.len() + 2) / 3 * 4);
+        for chunk in input.chunks(3) {
+            let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+            let triple = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    output.push(BASE64_ALPHABET[(triple >> (18 - 6 * i) & 0x3f) as usize]);
+                } else {
+                    output.push(b'=');
+                }
+            }
+        }
+        output
+    }
+
+    // owner supplied colors are expected to be utf-8
+    fn as_str(value: &[u8]) -> &str {
+        core::str::from_utf8(value).unwrap_or_default()
+    }
+
+    // appends `value` as a quoted JSON string
+    fn push_json_string(json: &mut String, value: &[u8]) {
+        json.push(b'"');
+        for &byte in value {
+            match byte {
+                b'"' | b'\\' => json.extend_from_slice(&[b'\\', byte]),
+                b'\n' => json.extend_from_slice(b"\\n"),
+                0..=0x1f => json.extend_from_slice(format!("\\u{:04x}", byte).as_bytes()),
+                _ => json.push(byte),
+            }
+        }
+        json.push(b'"');
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -718,97 +2504,768 @@ pub mod my_psp34_mintable {
             test::default_accounts::<ink::env::DefaultEnvironment>()
         }
 
-        fn set_caller(sender: AccountId) {
-            ink::env::test::set_caller::<Environment>(sender);
-        }
-        fn set_block_timestamp(timestamp: u64) {
-            ink::env::test::set_block_timestamp::<Environment>(timestamp);
+        fn set_caller(sender: AccountId) {
+            ink::env::test::set_caller::<Environment>(sender);
+        }
+        fn set_block_timestamp(timestamp: u64) {
+            ink::env::test::set_block_timestamp::<Environment>(timestamp);
+        }
+
+        type Event = <Contract as ::ink::reflect::ContractEventBase>::Type;
+
+        fn decoded_events() -> ink::prelude::vec::Vec<Event> {
+            test::recorded_events()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect()
+        }
+        // #[ink_e2e::test]
+        // async fn get_and_set() -> E2EResult<()> {
+        //     let constructor = ContractRef::new();
+        //     Ok(())
+        // }
+
+        #[ink::test]
+        fn default_apple_value() {
+            let contract = Contract::new();
+            let account = AccountId::from([0x0; 32]);
+            assert_eq!(contract.get_your_apple(account), 0);
+        }
+
+        #[ink::test]
+        fn set_and_get_apple() {
+            let mut contract = Contract::new();
+            let account = AccountId::from([0x1; 32]);
+            contract.set_your_apple(account, 10);
+            assert_eq!(contract.get_your_apple(account), 10);
+        }
+        #[ink::test]
+        fn set_default_works() {
+            let accounts = default_accounts();
+            let mut contract = Contract::new_with_owner(accounts.alice);
+
+            set_caller(accounts.alice);
+            assert!(contract.set_default(accounts.alice.clone()).is_ok());
+
+            assert_eq!(contract.get_bad_uri(), String::from("ipfs://QmV1VxGsrM4MLNn1qwR9Hmu5DGFfWjzHmhHFXpTT2fevMQ/"));
+            assert_eq!(contract.get_normal_uri(), String::from("ipfs://QmTBf9GJLiw97v84Q7aEPPFHUXdyqXWC6AUp97VnLFZtWr/"));
+            assert_eq!(contract.get_good_uri(), String::from("ipfs://QmQUxL1RSWbZAWhQfWnJJrMVZsPm4Stc5C64kRuSnXe56Q/"));
+            assert_eq!(contract.get_your_apple(accounts.alice.clone()), 10);
+            assert_eq!(contract.get_your_money(accounts.alice.clone()), 500);
+        }
+        
+        #[ink::test]
+        fn get_current_status_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+             // mint a new token
+            assert!(contract.mint(accounts.alice, Id::U64(token_id).clone()).is_ok());
+
+            set_block_timestamp(1 * 1000);
+            contract.set_status(token_id.clone(), 100, 100, 100).unwrap();
+            let initial_status = contract.get_status(token_id.clone()).unwrap();
+            assert_eq!(initial_status, Status { hungry: 100, health: 100, happy: 100 });
+
+            // get current time. but return 0 in test environment
+            // let current_time = ink::env::block_timestamp::<ink::env::DefaultEnvironment>().into();
+        
+            // assume already eaten an apple at 1 second
+            contract.set_last_eaten(Id::U64(token_id).clone(), 1 * 1000); // 1 second
+
+            // Let's simulate the passage of time
+            set_block_timestamp(61 * 1000); // 61 seconds
+            let status_after_time = contract.get_current_status(token_id.clone()).unwrap();
+            
+            // // We need to manually calculate the expected new statuses because they are time-dependent
+            let expected_status = Status {
+                hungry: 100, // 100 + 5 (1 minute passed, so status increases by 5), but not more than 100
+                health: 95, // 100 - 5
+                happy: 95, // 100 - 5
+            };
+            assert_eq!(status_after_time, expected_status);
+
+            let total_status = contract.get_total_status(token_id.clone());
+
+            assert_eq!(total_status, 90); // 95 + 95 - 100
+
+            set_block_timestamp(6000 * 1000); // 6000 seconds (100 minutes)
+
+            let status_after_many_time_passed = contract.get_current_status(token_id.clone()).unwrap();
+
+            let expected_status_many_time_passed = Status {
+                hungry: 100, // 100 + 5 * 99 (100 minute passed, so status increases by 5), but not more than 100
+                health: 0, // 100 - 5 * 99 , but not less than 0
+                happy: 0, // 100 - 5 * 99 , but not less than 0
+            };
+            assert_eq!(status_after_many_time_passed, expected_status_many_time_passed);
+
+            let total_status_many_time_passed = contract.get_total_status(token_id.clone());
+
+            assert_eq!(total_status_many_time_passed, 0); // 0 + 0 - 100, but not less than 0
+
+        }
+
+        #[ink::test]
+        fn decay_follows_config() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            assert_eq!(contract.get_decay_config(), DecayConfig::default());
+
+            contract.set_status(token_id, 50, 50, 50).unwrap();
+            contract.set_last_eaten(Id::U64(token_id), 1 * 1000);
+            set_block_timestamp(31 * 1000);
+
+            // 3 points every 10 seconds; happy now rises and health stays put
+            let config = DecayConfig {
+                interval: 10 * 1000,
+                hungry: StatDecay { rate: 3, direction: DecayDirection::Increase },
+                health: StatDecay { rate: 0, direction: DecayDirection::Decrease },
+                happy: StatDecay { rate: 3, direction: DecayDirection::Increase },
+            };
+            assert!(contract.set_decay_config(config).is_ok());
+            assert_eq!(contract.get_decay_config(), config);
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 59, health: 50, happy: 59 }));
+
+            let events = decoded_events();
+            assert!(matches!(
+                events.last(),
+                Some(Event::DecayConfigChanged(DecayConfigChanged { config: changed })) if *changed == config
+            ));
+
+            assert_eq!(
+                contract.set_decay_config(DecayConfig { interval: 0, ..config }),
+                Err(ContractError::InvalidDecayConfig)
+            );
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_decay_config(DecayConfig::default()), Err(ContractError::OwnableError));
+            assert_eq!(contract.get_decay_config(), config);
+        }
+
+        #[ink::test]
+        fn settling_never_skips_or_repeats_decay() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            assert_eq!(contract.get_last_updated(token_id), Some(0));
+
+            // settling at odd times keeps the partial interval
+            for time in [30, 90, 100, 150, 230] {
+                set_block_timestamp(time * 1000);
+                assert!(contract.settle_status(token_id).is_ok());
+                // settling twice at the same time changes nothing
+                let settled = contract.get_status(token_id);
+                assert!(contract.settle_status(token_id).is_ok());
+                assert_eq!(contract.get_status(token_id), settled);
+                assert_eq!(contract.get_current_status(token_id), settled);
+            }
+            // 3 whole minutes passed, the last one started at 180 seconds
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 15, health: 85, happy: 85 }));
+            assert_eq!(contract.get_last_updated(token_id), Some(180 * 1000));
+
+            set_block_timestamp(240 * 1000);
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 20, health: 80, happy: 80 }));
+        }
+
+        #[ink::test]
+        fn changes_apply_on_top_of_settled_decay() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 50, 50, 50).unwrap();
+
+            // 2 minutes of decay, then +10
+            set_block_timestamp(150 * 1000);
+            assert!(contract.change_some_status(token_id, 10).is_ok());
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 50, health: 50, happy: 50 }));
+            assert_eq!(contract.get_last_updated(token_id), Some(120 * 1000));
+
+            // the 30 seconds before the change still count
+            set_block_timestamp(180 * 1000);
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 55, health: 45, happy: 45 }));
+
+            // eating doesn't reset the decay clock
+            contract.set_last_eaten(Id::U64(token_id), 180 * 1000);
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 55, health: 45, happy: 45 }));
+
+            assert_eq!(contract.settle_status(2), Err(PSP34Error::TokenNotExists));
+        }
+
+        #[ink::test]
+        fn migrate_checkpoints_statuses_at_last_meal() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            assert!(contract.mint(accounts.alice, Id::U64(1)).is_ok());
+            assert!(contract.mint(accounts.alice, Id::U64(2)).is_ok());
+            contract.current_token_id = 2;
+            contract.storage_version.set(&2);
+            contract.asset_status.insert(&Id::U64(1), &Status { hungry: 0, health: 100, happy: 100 });
+            contract.last_eaten.insert(&Id::U64(1), &(60 * 1000));
+
+            set_block_timestamp(200 * 1000);
+            assert_eq!(contract.migrate(10), Ok(true));
+            assert_eq!(contract.get_last_updated(1), Some(60 * 1000));
+            // no status, nothing to decay
+            assert_eq!(contract.get_last_updated(2), None);
+            assert_eq!(contract.get_current_status(1), Some(Status { hungry: 10, health: 90, happy: 90 }));
+            assert_eq!(contract.asset_status.get(&Id::U64(1)), None);
+        }
+
+        #[ink::test]
+        fn stats_stay_within_caps() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+
+            contract.set_status(token_id, 500, 500, 500).unwrap();
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 100, health: 100, happy: 100 }));
+
+            let caps = StatCaps {
+                hungry: StatBounds { min: 10, max: 80 },
+                health: StatBounds { min: 0, max: 150 },
+                happy: StatBounds { min: 20, max: 120 },
+            };
+            assert!(contract.set_stat_caps(caps).is_ok());
+            assert_eq!(contract.get_stat_caps(), caps);
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            assert!(contract.change_some_status(token_id, 30).is_ok());
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 10, health: 130, happy: 120 }));
+
+            // a long time later everything sits at its bound
+            set_block_timestamp(1000 * 60 * 1000);
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 80, health: 0, happy: 20 }));
+
+            let invalid = StatCaps { hungry: StatBounds { min: 50, max: 40 }, ..caps };
+            assert_eq!(contract.set_stat_caps(invalid), Err(ContractError::InvalidStatCaps));
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_stat_caps(StatCaps::default()), Err(ContractError::OwnableError));
+            assert_eq!(contract.set_lifecycle_config(LifecycleConfig::default()), Err(ContractError::OwnableError));
+        }
+
+        #[ink::test]
+        fn only_owner_can_set_status() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.bob, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+
+            // not even the pet's owner can push it into a critical state
+            for caller in [accounts.bob, accounts.charlie] {
+                set_caller(caller);
+                assert!(contract.set_status(token_id, 100, 0, 0).is_err());
+            }
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 0, health: 100, happy: 100 }));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Healthy);
+        }
+
+        #[ink::test]
+        fn pet_gets_sick_critical_and_dies() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            let minutes = |minutes: u64| minutes * 60 * 1000;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            assert_eq!(contract.get_pet_state(token_id), PetState::Healthy);
+
+            // health 30 after 14 minutes, 25 after 15
+            set_block_timestamp(minutes(14));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Healthy);
+            set_block_timestamp(minutes(15));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Sick);
+
+            // out of health after 20 minutes, dead an hour later
+            set_block_timestamp(minutes(20));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Critical);
+            // settling in between keeps the time it became critical
+            set_block_timestamp(minutes(30));
+            assert!(contract.settle_status(token_id).is_ok());
+            assert_eq!(contract.critical_since.get(&Id::U64(token_id)), Some(minutes(20)));
+            set_block_timestamp(minutes(79));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Critical);
+            set_block_timestamp(minutes(80));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Dead);
+
+            // dead pets can't eat or be cared for
+            contract.set_your_apple(accounts.alice, 1);
+            assert_eq!(contract.eat_an_apple(token_id, accounts.alice), Err(ContractError::PetIsDead));
+            assert!(contract.change_some_status(token_id, 10).is_err());
+            assert!(contract.set_status(token_id, 0, 100, 100).is_err());
+            assert_eq!(contract.died_at.get(&Id::U64(token_id)), Some(minutes(80)));
+            assert_eq!(contract.get_your_apple(accounts.alice), 1);
+        }
+
+        #[ink::test]
+        fn care_stops_a_critical_pet_from_dying() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            let minutes = |minutes: u64| minutes * 60 * 1000;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+
+            set_block_timestamp(minutes(50));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Critical);
+            assert!(contract.change_some_status(token_id, 50).is_ok());
+            assert_eq!(contract.get_pet_state(token_id), PetState::Healthy);
+            assert_eq!(contract.critical_since.get(&Id::U64(token_id)), None);
+
+            // critical again 10 minutes later, so it only dies 70 minutes from now
+            set_block_timestamp(minutes(119));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Critical);
+            set_block_timestamp(minutes(120));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Dead);
+        }
+
+        #[ink::test]
+        fn revive_brings_dead_pets_back() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+
+            assert_eq!(contract.revive(token_id, RevivePayment::GameMoney), Err(ContractError::PetIsNotDead));
+
+            set_block_timestamp(200 * 60 * 1000);
+            assert_eq!(contract.revive(token_id, RevivePayment::GameMoney), Err(ContractError::NotEnoughMoney));
+            assert_eq!(contract.revive(token_id, RevivePayment::Apples), Err(ContractError::NotEnoughApple));
+
+            contract.set_your_money(accounts.alice, 250);
+            assert!(contract.revive(token_id, RevivePayment::GameMoney).is_ok());
+            assert_eq!(contract.get_your_money(accounts.alice), 50);
+            assert_eq!(contract.get_pet_state(token_id), PetState::Healthy);
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 0, health: 50, happy: 50 }));
+            assert!(matches!(
+                decoded_events().last(),
+                Some(Event::PetRevived(PetRevived { token_id: 1, payment: RevivePayment::GameMoney, .. }))
+            ));
+
+            // dies again, this time paid with apples
+            set_block_timestamp(400 * 60 * 1000);
+            contract.set_your_apple(accounts.alice, 4);
+            assert!(contract.revive(token_id, RevivePayment::Apples).is_ok());
+            assert_eq!(contract.get_your_apple(accounts.alice), 1);
+            assert_eq!(contract.get_pet_state(token_id), PetState::Healthy);
+        }
+
+        // a contract where pets neither decay nor die, so only xp changes over days
+        fn ageless_contract(owner: AccountId) -> Contract {
+            let mut contract = Contract::new_with_owner(owner);
+            let decay = StatDecay { rate: 0, direction: DecayDirection::Decrease };
+            contract.decay_config.set(&DecayConfig { interval: 60 * 1000, hungry: decay, health: decay, happy: decay });
+            contract.lifecycle.set(&LifecycleConfig { death_delay: u64::MAX, ..Default::default() });
+            contract
+        }
+
+        // two pets of alice that neither decay nor die, with the next token id after them
+        fn breeding_contract(owner: AccountId) -> Contract {
+            let mut contract = ageless_contract(owner);
+            assert!(contract.mint(owner, Id::U64(1)).is_ok());
+            assert!(contract.mint(owner, Id::U64(2)).is_ok());
+            contract.current_token_id = 2;
+            contract.set_status(1, 0, 100, 100).unwrap();
+            contract.set_status(2, 20, 60, 80).unwrap();
+            contract
+        }
+
+        #[ink::test]
+        fn breed_mints_a_child_of_both_parents() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = breeding_contract(accounts.alice);
+            contract.set_your_money(accounts.alice, 500);
+
+            assert_eq!(contract.breed(1, 1, BreedPayment::GameMoney), Err(ContractError::InvalidParents));
+            assert_eq!(contract.breed(1, 9, BreedPayment::GameMoney), Err(ContractError::PSP34Error));
+
+            assert_eq!(contract.breed(1, 2, BreedPayment::GameMoney), Ok(3));
+            assert_eq!(contract.owner_of(Id::U64(3)), Some(accounts.alice));
+            assert_eq!(contract.current_token_id, 3);
+            assert_eq!(contract.get_your_money(accounts.alice), 300);
+            assert_eq!(contract.get_lineage(3), Some(Lineage { parent_a: 1, parent_b: 2, generation: 1 }));
+            assert_eq!(contract.get_breed_count(1), 1);
+            assert_eq!(contract.get_last_bred(2), Some(0));
+
+            // around the parents' average of 10, 80 and 90
+            let status = contract.get_status(3).unwrap();
+            assert!(status.hungry <= 20);
+            assert!((70..=90).contains(&status.health));
+            assert!((80..=100).contains(&status.happy));
+            assert!(matches!(
+                decoded_events().last(),
+                Some(Event::PetBred(PetBred { parent_a: 1, parent_b: 2, child: 3, .. }))
+            ));
+
+            // the child of a bred pet is one generation further
+            set_block_timestamp(ONE_DAY);
+            assert_eq!(contract.breed(3, 1, BreedPayment::GameMoney), Ok(4));
+            assert_eq!(contract.get_lineage(4).map(|lineage| lineage.generation), Some(2));
+            assert_eq!(contract.get_your_money(accounts.alice), 100);
+
+            // the family tree can be walked back from any pet
+            assert_eq!(contract.get_parents(4), Some((3, 1)));
+            assert_eq!(contract.get_parents(3), Some((1, 2)));
+            assert_eq!(contract.get_parents(1), None);
+        }
+
+        #[ink::test]
+        fn breed_enforces_cooldown_and_limit() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = breeding_contract(accounts.alice);
+            contract.set_your_money(accounts.alice, 10000);
+            let config = BreedConfig { max_breeds: 2, ..Default::default() };
+            assert!(contract.set_breed_config(config).is_ok());
+            assert_eq!(contract.get_breed_config(), config);
+
+            assert!(contract.breed(1, 2, BreedPayment::GameMoney).is_ok());
+            set_block_timestamp(ONE_DAY - 1);
+            assert_eq!(contract.breed(1, 2, BreedPayment::GameMoney), Err(ContractError::BreedingCooldown));
+            set_block_timestamp(ONE_DAY);
+            assert!(contract.breed(1, 2, BreedPayment::GameMoney).is_ok());
+            set_block_timestamp(2 * ONE_DAY);
+            assert_eq!(contract.breed(1, 2, BreedPayment::GameMoney), Err(ContractError::MaxBreedsReached));
+
+            contract.set_your_money(accounts.alice, 100);
+            assert_eq!(contract.breed(3, 4, BreedPayment::GameMoney), Err(ContractError::NotEnoughMoney));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_breed_config(BreedConfig::default()), Err(ContractError::OwnableError));
+        }
+
+        #[ink::test]
+        fn fresh_deploy_takes_payments_before_a_token_is_set() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let contract = Contract::new();
+
+            assert_eq!(contract.get_payment_token(), None);
+            assert_eq!(contract.ensure_payment_token(accounts.django), Ok(()));
+            assert_eq!(contract.ensure_payment_token(accounts.eve), Ok(()));
+        }
+
+        #[ink::test]
+        fn psp22_payments_only_take_the_payment_token() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = breeding_contract(accounts.alice);
+
+            assert!(contract.set_payment_token(accounts.django).is_ok());
+            assert_eq!(contract.get_payment_token(), Some(accounts.django));
+            assert_eq!(
+                contract.breed(1, 2, BreedPayment::Psp22(accounts.eve)),
+                Err(ContractError::UnknownPaymentToken)
+            );
+            assert_eq!(
+                contract.buy_game_money(accounts.eve, accounts.alice, String::new()),
+                Err(ContractError::UnknownPaymentToken)
+            );
+            assert_eq!(contract.owner_of(Id::U64(3)), None);
+            assert_eq!(contract.get_your_money(accounts.alice), 0);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_payment_token(accounts.eve), Err(ContractError::OwnableError));
+            assert_eq!(contract.get_payment_token(), Some(accounts.django));
+        }
+
+        #[ink::test]
+        fn breed_needs_ownership_or_approval() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = breeding_contract(accounts.alice);
+            contract.set_your_money(accounts.bob, 200);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.breed(1, 2, BreedPayment::GameMoney), Err(ContractError::NotApproved));
+
+            set_caller(accounts.alice);
+            assert!(contract.approve(accounts.bob, Some(Id::U64(1)), true).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(contract.breed(1, 2, BreedPayment::GameMoney), Err(ContractError::NotApproved));
+
+            set_caller(accounts.alice);
+            assert!(contract.approve(accounts.bob, Some(Id::U64(2)), true).is_ok());
+            set_caller(accounts.bob);
+            assert_eq!(contract.breed(1, 2, BreedPayment::GameMoney), Ok(3));
+            assert_eq!(contract.owner_of(Id::U64(3)), Some(accounts.bob));
+            assert_eq!(contract.get_your_money(accounts.bob), 0);
+        }
+
+        #[ink::test]
+        fn pets_evolve_when_requirements_are_met() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            assert_eq!(contract.get_born_at(token_id), Some(0));
+            assert_eq!(contract.get_stage(token_id), Stage::Egg);
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+
+            // hatching takes an hour and 50 game money
+            set_block_timestamp(30 * 60 * 1000);
+            assert_eq!(contract.evolve(token_id), Err(ContractError::TimeHasNotPassed));
+            set_block_timestamp(60 * 60 * 1000);
+            assert_eq!(contract.evolve(token_id), Err(ContractError::NotEnoughMoney));
+            contract.set_your_money(accounts.alice, 450);
+            assert!(contract.evolve(token_id).is_ok());
+            assert_eq!(contract.get_stage(token_id), Stage::Baby);
+            assert_eq!(contract.get_your_money(accounts.alice), 400);
+            assert!(matches!(
+                decoded_events().last(),
+                Some(Event::PetEvolved(PetEvolved { token_id: 1, stage: Stage::Baby }))
+            ));
+
+            set_block_timestamp(ONE_DAY);
+            assert_eq!(contract.evolve(token_id), Err(ContractError::LevelTooLow));
+            contract.gain_xp(token_id, 100);
+            assert!(contract.evolve(token_id).is_ok());
+            assert_eq!(contract.get_stage(token_id), Stage::Adult);
+
+            // elders need level 4 and at least a normal condition
+            set_block_timestamp(7 * ONE_DAY);
+            contract.gain_xp(token_id, 500);
+            contract.set_status(token_id, 100, 0, 0).unwrap();
+            assert_eq!(contract.evolve(token_id), Err(ContractError::ConditionTooLow));
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            assert!(contract.evolve(token_id).is_ok());
+            assert_eq!(contract.get_stage(token_id), Stage::Elder);
+            assert_eq!(contract.get_your_money(accounts.alice), 0);
+
+            assert_eq!(contract.evolve(token_id), Err(ContractError::MaxStageReached));
         }
-        // #[ink_e2e::test]
-        // async fn get_and_set() -> E2EResult<()> {
-        //     let constructor = ContractRef::new();
-        //     Ok(())
-        // }
 
         #[ink::test]
-        fn default_apple_value() {
-            let contract = Contract::new();
-            let account = AccountId::from([0x0; 32]);
-            assert_eq!(contract.get_your_apple(account), 0);
+        fn evolve_needs_ownership_or_approval() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            contract.set_your_money(accounts.bob, 50);
+            set_block_timestamp(60 * 60 * 1000);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.evolve(token_id), Err(ContractError::NotApproved));
+            assert_eq!(contract.get_stage(token_id), Stage::Egg);
+            assert_eq!(contract.get_your_money(accounts.bob), 50);
+
+            set_caller(accounts.alice);
+            assert!(contract.approve(accounts.bob, Some(Id::U64(token_id)), true).is_ok());
+            set_caller(accounts.bob);
+            assert!(contract.evolve(token_id).is_ok());
+            assert_eq!(contract.get_stage(token_id), Stage::Baby);
+            assert_eq!(contract.get_your_money(accounts.bob), 0);
         }
 
         #[ink::test]
-        fn set_and_get_apple() {
-            let mut contract = Contract::new();
-            let account = AccountId::from([0x1; 32]);
-            contract.set_your_apple(account, 10);
-            assert_eq!(contract.get_your_apple(account), 10);
+        fn token_uri_uses_stage_uri() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            assert!(contract.set_good_uri(String::from("ipfs://good/")).is_ok());
+            assert!(contract.set_stage_uri(Stage::Baby, 2, String::from("ipfs://baby-good/")).is_ok());
+
+            assert_eq!(contract.token_uri(token_id), String::from("ipfs://good/1"));
+            contract.stages.insert(&Id::U64(token_id), &Stage::Baby);
+            assert_eq!(contract.token_uri(token_id), String::from("ipfs://baby-good/1"));
+
+            // other conditions of the stage fall back to the tier uri
+            contract.set_status(token_id, 0, 50, 50).unwrap();
+            assert!(contract.set_normal_uri(String::from("ipfs://normal/")).is_ok());
+            assert_eq!(contract.token_uri(token_id), String::from("ipfs://normal/1"));
+
+            assert!(contract.set_stage_uri(Stage::Baby, 2, String::new()).is_ok());
+            assert_eq!(contract.get_stage_uri(Stage::Baby, 2), None);
+
+            set_caller(accounts.bob);
+            assert!(contract.set_stage_uri(Stage::Adult, 0, String::from("ipfs://mine/")).is_err());
+            assert_eq!(
+                contract.set_evolution_requirements(Contract::default_evolution_requirements()),
+                Err(ContractError::OwnableError)
+            );
+            set_caller(accounts.alice);
+            assert_eq!(
+                contract.set_evolution_requirements(Vec::new()),
+                Err(ContractError::InvalidEvolutionRequirements)
+            );
         }
+
         #[ink::test]
-        fn set_default_works() {
+        fn eating_grants_xp_and_daily_streaks() {
             let accounts = default_accounts();
-            let mut contract = Contract::new_with_owner(accounts.alice);
-
             set_caller(accounts.alice);
-            assert!(contract.set_default(accounts.alice.clone()).is_ok());
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_your_apple(accounts.alice, 4);
 
-            assert_eq!(contract.get_bad_uri(), String::from("ipfs://QmV1VxGsrM4MLNn1qwR9Hmu5DGFfWjzHmhHFXpTT2fevMQ/"));
-            assert_eq!(contract.get_normal_uri(), String::from("ipfs://QmTBf9GJLiw97v84Q7aEPPFHUXdyqXWC6AUp97VnLFZtWr/"));
-            assert_eq!(contract.get_good_uri(), String::from("ipfs://QmQUxL1RSWbZAWhQfWnJJrMVZsPm4Stc5C64kRuSnXe56Q/"));
-            assert_eq!(contract.get_your_apple(accounts.alice.clone()), 10);
-            assert_eq!(contract.get_your_money(accounts.alice.clone()), 500);
+            // 10 for eating and 20 for the first care of the day
+            set_block_timestamp(100 * 1000);
+            assert!(contract.eat_an_apple(token_id, accounts.alice).is_ok());
+            assert_eq!(contract.get_xp(token_id), 30);
+
+            // no daily bonus twice on the same day
+            set_block_timestamp(200 * 1000);
+            assert!(contract.eat_an_apple(token_id, accounts.alice).is_ok());
+            assert_eq!(contract.get_xp(token_id), 40);
+
+            // the next day the streak doubles the bonus
+            set_block_timestamp(ONE_DAY + 100 * 1000);
+            assert!(contract.eat_an_apple(token_id, accounts.alice).is_ok());
+            assert_eq!(contract.get_xp(token_id), 90);
+            assert_eq!(contract.get_care_streak(token_id), Some(CareStreak { day: 1, streak: 2 }));
+            assert_eq!(contract.get_level(token_id), 1);
+
+            // skipping a day resets the streak
+            set_block_timestamp(3 * ONE_DAY);
+            assert!(contract.eat_an_apple(token_id, accounts.alice).is_ok());
+            assert_eq!(contract.get_xp(token_id), 120);
+            assert_eq!(contract.get_care_streak(token_id), Some(CareStreak { day: 3, streak: 1 }));
+            assert_eq!(contract.get_level(token_id), 2);
+            assert!(matches!(
+                decoded_events().last(),
+                Some(Event::LevelUp(LevelUp { token_id: 1, level: 2 }))
+            ));
         }
-        
+
         #[ink::test]
-        fn get_current_status_works() {
+        fn eating_needs_own_apples_and_ownership_or_approval() {
             let accounts = default_accounts();
             set_caller(accounts.alice);
-            let mut contract = Contract::new_with_owner(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
             let token_id: u64 = 1;
-             // mint a new token
-            assert!(contract.mint(accounts.alice, Id::U64(token_id).clone()).is_ok());
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 50, 50, 50).unwrap();
+            contract.set_your_apple(accounts.alice, 1);
+            contract.set_your_apple(accounts.bob, 1);
+            set_block_timestamp(600 * 1000);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.eat_an_apple(token_id, accounts.alice), Err(ContractError::InvalidAccountId));
+            assert_eq!(contract.eat_an_apple(token_id, accounts.bob), Err(ContractError::NotApproved));
+            assert_eq!(contract.get_your_apple(accounts.alice), 1);
+            assert_eq!(contract.get_your_apple(accounts.bob), 1);
+            assert_eq!(contract.get_xp(token_id), 0);
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 50, health: 50, happy: 50 }));
 
-            contract.set_status(token_id.clone(), 100, 100, 100).unwrap();
-            let initial_status = contract.get_status(token_id.clone()).unwrap();
-            assert_eq!(initial_status, Status { hungry: 100, health: 100, happy: 100 });
+            set_caller(accounts.alice);
+            assert!(contract.approve(accounts.bob, Some(Id::U64(token_id)), true).is_ok());
+            set_caller(accounts.bob);
+            assert!(contract.eat_an_apple(token_id, accounts.bob).is_ok());
+            assert_eq!(contract.get_your_apple(accounts.bob), 0);
+            assert_eq!(contract.get_your_apple(accounts.alice), 1);
+        }
 
-            // get current time. but return 0 in test environment
-            // let current_time = ink::env::block_timestamp::<ink::env::DefaultEnvironment>().into();
-        
-            // assume already eaten an apple at 1 second
-            contract.set_last_eaten(Id::U64(token_id).clone(), 1 * 1000); // 1 second
+        #[ink::test]
+        fn playing_makes_pets_happier() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 50, 50).unwrap();
 
-            // Let's simulate the passage of time
-            set_block_timestamp(61 * 1000); // 61 seconds
-            let status_after_time = contract.get_current_status(token_id.clone()).unwrap();
-            
-            // // We need to manually calculate the expected new statuses because they are time-dependent
-            let expected_status = Status {
-                hungry: 105, // 100 + 5 (1 minute passed, so status increases by 5)
-                health: 95, // 100 - 5
-                happy: 95, // 100 - 5
-            };
-            assert_eq!(status_after_time, expected_status);
+            set_block_timestamp(100 * 1000);
+            assert!(contract.play_with_pet(token_id).is_ok());
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 0, health: 50, happy: 60 }));
+            assert_eq!(contract.get_xp(token_id), 25);
 
-            let total_status = contract.get_total_status(token_id.clone());
+            set_block_timestamp(130 * 1000);
+            assert_eq!(contract.play_with_pet(token_id), Err(ContractError::TimeHasNotPassed));
+            assert_eq!(contract.play_with_pet(2), Err(ContractError::PSP34Error));
+        }
 
-            assert_eq!(total_status, 85); // 95 + 95 - 105
+        #[ink::test]
+        fn play_needs_ownership_or_approval() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = ageless_contract(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 50, 50).unwrap();
+            set_block_timestamp(100 * 1000);
 
-            set_block_timestamp(6000 * 1000); // 6000 seconds (100 minutes)
+            set_caller(accounts.bob);
+            assert_eq!(contract.play_with_pet(token_id), Err(ContractError::NotApproved));
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 0, health: 50, happy: 50 }));
+            assert_eq!(contract.get_xp(token_id), 0);
 
-            let status_after_many_time_passed = contract.get_current_status(token_id.clone()).unwrap();
+            set_caller(accounts.alice);
+            assert!(contract.approve(accounts.bob, Some(Id::U64(token_id)), true).is_ok());
+            set_caller(accounts.bob);
+            assert!(contract.play_with_pet(token_id).is_ok());
+            assert_eq!(contract.get_current_status(token_id), Some(Status { hungry: 0, health: 50, happy: 60 }));
+        }
 
-            let expected_status_many_time_passed = Status {
-                hungry: 595, // 100 + 5 * 99 (100 minute passed, so status increases by 5)
-                health: 0, // 100 - 5 * 99 , but not less than 0
-                happy: 0, // 100 - 5 * 99 , but not less than 0
-            };
-            assert_eq!(status_after_many_time_passed, expected_status_many_time_passed);
+        #[ink::test]
+        fn levels_raise_stat_caps() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            assert_eq!(contract.get_level(token_id), 1);
+            assert_eq!(contract.get_attribute(Id::U64(token_id), String::from("level")), Some(String::from("1")));
+
+            contract.set_status(token_id, 0, 500, 500).unwrap();
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 0, health: 100, happy: 100 }));
+
+            contract.gain_xp(token_id, 300);
+            assert_eq!(contract.get_level(token_id), 3);
+            assert_eq!(contract.get_attribute(Id::U64(token_id), String::from("level")), Some(String::from("3")));
+            assert_eq!(contract.caps_of(token_id).health.max, 120);
+            contract.set_status(token_id, 0, 500, 500).unwrap();
+            assert_eq!(contract.get_status(token_id), Some(Status { hungry: 0, health: 120, happy: 120 }));
+
+            assert!(contract.set_level_config(vec![1000], 50).is_ok());
+            assert_eq!(contract.get_level(token_id), 1);
+            assert_eq!(contract.caps_of(token_id).health.max, 100);
+        }
 
-            let total_status_many_time_passed = contract.get_total_status(token_id.clone());
+        #[ink::test]
+        fn level_config_is_validated_and_owned() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+
+            assert_eq!(
+                contract.set_level_config(vec![100, 100], 10),
+                Err(ContractError::InvalidLevelThresholds)
+            );
+            assert_eq!(contract.get_level_thresholds(), vec![100, 300, 600, 1000]);
 
-            assert_eq!(total_status_many_time_passed, 0); // 0 + 0 - 595, but not less than 0
+            let rewards = XpRewards { eat: 1, play: 2, daily_care: 3, max_streak: 4 };
+            assert!(contract.set_xp_rewards(rewards).is_ok());
+            assert_eq!(contract.get_xp_rewards(), rewards);
 
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_level_config(Vec::new(), 0), Err(ContractError::OwnableError));
+            assert_eq!(contract.set_xp_rewards(XpRewards::default()), Err(ContractError::OwnableError));
         }
 
         #[ink::test]
@@ -880,6 +3337,536 @@ pub mod my_psp34_mintable {
             
             assert_eq!(contract.get_your_apple(accounts.alice), 1);
         }
+
+        #[ink::test]
+        fn claim_a_nft_emits_event() {
+            let accounts = default_accounts();
+            set_caller(accounts.bob);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+
+            assert!(contract.claim_a_nft().is_ok());
+
+            let events = decoded_events();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                &events[0],
+                Event::NftClaimed(NftClaimed { owner, token_id: 1 }) if *owner == accounts.bob
+            ));
+        }
+
+        #[ink::test]
+        fn buy_and_eat_an_apple_emit_events() {
+            let mut contract = Contract::default();
+            let accounts = test::default_accounts::<Environment>();
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id).clone()).is_ok());
+
+            contract.set_your_money(accounts.alice, 50);
+            contract.buy_an_apple(accounts.alice).unwrap();
+            contract.set_last_eaten(Id::U64(token_id).clone(), 1 * 1000);
+            set_block_timestamp(6000 * 1000);
+            contract.eat_an_apple(token_id, accounts.alice).unwrap();
+
+            let events = decoded_events();
+            // the purchase, the status change, the eaten apple and the xp for it
+            assert_eq!(events.len(), 4);
+            assert!(matches!(
+                &events[0],
+                Event::ApplePurchased(ApplePurchased { account, price: 20, apples: 1 }) if *account == accounts.alice
+            ));
+            let changed_status = match &events[1] {
+                Event::StatusChanged(StatusChanged { token_id: 1, status }) => status.clone(),
+                _ => panic!("encountered unexpected event kind: expected a StatusChanged event"),
+            };
+            assert_eq!(Some(changed_status), contract.get_status(token_id));
+            assert!(matches!(
+                &events[2],
+                Event::AppleEaten(AppleEaten { account, token_id: 1, .. }) if *account == accounts.alice
+            ));
+            assert!(matches!(&events[3], Event::XpGained(XpGained { token_id: 1, amount: 30, xp: 30 })));
+        }
+
+        #[ink::test]
+        fn money_messages_emit_events() {
+            let mut contract = Contract::default();
+            let accounts = test::default_accounts::<Environment>();
+
+            set_block_timestamp(100 * 1000);
+            assert!(contract.daily_bonus(accounts.alice).is_ok());
+            assert!(contract.stake_your_money(accounts.alice, 60).is_ok());
+            assert!(contract.withdraw_your_money(accounts.alice).is_ok());
+
+            let events = decoded_events();
+            assert_eq!(events.len(), 3);
+            assert!(matches!(&events[0], Event::DailyBonusClaimed(DailyBonusClaimed { amount: 100, .. })));
+            assert!(matches!(&events[1], Event::MoneyStaked(MoneyStaked { amount: 60, .. })));
+            assert!(matches!(&events[2], Event::MoneyWithdrawn(MoneyWithdrawn { amount: 60, .. })));
+        }
+
+        #[ink::test]
+        fn set_uri_emits_event() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+
+            assert!(contract.set_good_uri(String::from("ipfs://good/")).is_ok());
+
+            let events = decoded_events();
+            assert_eq!(events.len(), 1);
+            assert!(matches!(
+                &events[0],
+                Event::UriChanged(UriChanged { kind: UriKind::Good, uri }) if *uri == String::from("ipfs://good/")
+            ));
+        }
+
+        #[ink::test]
+        fn get_attribute_returns_live_status() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            contract.set_last_eaten(Id::U64(token_id), 1 * 1000);
+            set_block_timestamp(61 * 1000);
+
+            let attribute = |key: &str| contract.get_attribute(Id::U64(token_id), String::from(key));
+            assert_eq!(attribute("hungry"), Some(String::from("5")));
+            assert_eq!(attribute("health"), Some(String::from("95")));
+            assert_eq!(attribute("happy"), Some(String::from("95")));
+            // 95 + 95 - 5 = 185
+            assert_eq!(attribute("condition"), Some(String::from("normal")));
+
+            // many minutes later the pet is in bad condition
+            set_block_timestamp(6000 * 1000);
+            assert_eq!(
+                contract.get_attribute(Id::U64(token_id), String::from("condition")),
+                Some(String::from("bad"))
+            );
+            assert_eq!(contract.get_attribute(Id::U64(token_id), String::from("health")), Some(String::from("0")));
+
+            // tokens that don't exist have no attributes
+            assert_eq!(contract.get_attribute(Id::U64(2), String::from("hungry")), None);
+        }
+
+        #[ink::test]
+        fn collection_and_token_attributes_work() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+
+            assert!(contract.set_collection_metadata(String::from("Pets"), String::from("PET")).is_ok());
+            let collection_id = contract.collection_id();
+            assert_eq!(contract.get_attribute(collection_id.clone(), String::from("name")), Some(String::from("Pets")));
+            assert_eq!(contract.get_attribute(collection_id, String::from("symbol")), Some(String::from("PET")));
+
+            assert!(contract.set_token_attribute(token_id, String::from("species"), String::from("cat")).is_ok());
+            assert_eq!(contract.get_attribute(Id::U64(token_id), String::from("species")), Some(String::from("cat")));
+            assert_eq!(
+                contract.set_token_attribute(token_id, String::from("happy"), String::from("1000")),
+                Err(ContractError::ReadOnlyAttribute)
+            );
+
+            set_caller(accounts.bob);
+            assert!(contract.set_collection_metadata(String::from("Mine"), String::from("MINE")).is_err());
+            assert_eq!(
+                contract.set_token_attribute(token_id, String::from("species"), String::from("dog")),
+                Err(ContractError::OwnableError)
+            );
+        }
+
+        #[ink::test]
+        fn base64_encode_works() {
+            assert_eq!(base64_encode(b""), String::from(""));
+            assert_eq!(base64_encode(b"f"), String::from("Zg=="));
+            assert_eq!(base64_encode(b"fo"), String::from("Zm8="));
+            assert_eq!(base64_encode(b"foo"), String::from("Zm9v"));
+            assert_eq!(base64_encode(b"foobar"), String::from("Zm9vYmFy"));
+        }
+
+        #[ink::test]
+        fn token_uri_switches_to_onchain_metadata() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            assert!(contract.set_normal_uri(String::from("ipfs://normal/")).is_ok());
+            assert!(contract.set_description(String::from("A \"hungry\" pet")).is_ok());
+
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            contract.set_last_eaten(Id::U64(token_id), 1 * 1000);
+            set_block_timestamp(61 * 1000);
+            assert_eq!(contract.token_uri(token_id), String::from("ipfs://normal/1"));
+
+            assert!(contract.set_metadata_mode(MetadataMode::OnChain).is_ok());
+            assert_eq!(contract.get_metadata_mode(), MetadataMode::OnChain);
+
+            let json = contract.get_token_json(token_id);
+            let image = contract.get_image_uri(token_id);
+            assert_eq!(
+                core::str::from_utf8(&json).unwrap(),
+                format!(
+                    "{{\"name\":\"Pet #1\",\"description\":\"A \\\"hungry\\\" pet\",\"image\":\"{}\",\"attributes\":[\
+                    {{\"trait_type\":\"hungry\",\"value\":5}},\
+                    {{\"trait_type\":\"health\",\"value\":95}},\
+                    {{\"trait_type\":\"happy\",\"value\":95}},\
+                    {{\"trait_type\":\"condition\",\"value\":\"normal\"}},\
+                    {{\"trait_type\":\"level\",\"value\":1}},\
+                    {{\"trait_type\":\"last_eaten\",\"display_type\":\"date\",\"value\":1}}]}}",
+                    as_str(&image)
+                )
+            );
+            let mut expected = String::from("data:application/json;base64,");
+            expected.extend_from_slice(&base64_encode(&json));
+            assert_eq!(contract.token_uri(token_id), expected);
+
+            let events = decoded_events();
+            assert!(matches!(
+                events.last(),
+                Some(Event::MetadataModeChanged(MetadataModeChanged { mode: MetadataMode::OnChain }))
+            ));
+
+            set_caller(accounts.bob);
+            assert!(contract.set_metadata_mode(MetadataMode::Ipfs).is_err());
+            assert!(contract.set_description(String::new()).is_err());
+            assert_eq!(contract.get_metadata_mode(), MetadataMode::OnChain);
+        }
+
+        #[ink::test]
+        fn get_svg_matches_snapshot() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            contract.set_last_eaten(Id::U64(token_id), 1 * 1000);
+            set_block_timestamp(61 * 1000);
+
+            assert_eq!(
+                as_str(&contract.get_svg(token_id)),
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 200 200\">\
+                <rect width=\"200\" height=\"200\" fill=\"#fff2b3\"/>\
+                <circle cx=\"100\" cy=\"80\" r=\"40\" fill=\"#f4a261\"/>\
+                <circle cx=\"86\" cy=\"72\" r=\"4\"/><circle cx=\"114\" cy=\"72\" r=\"4\"/>\
+                <path d=\"M86 96 L114 96\" stroke=\"#000\" stroke-width=\"3\" fill=\"none\"/>\
+                <rect x=\"50\" y=\"140\" width=\"5\" height=\"8\" fill=\"#e76f51\"/>\
+                <rect x=\"50\" y=\"155\" width=\"95\" height=\"8\" fill=\"#2a9d8f\"/>\
+                <rect x=\"50\" y=\"170\" width=\"95\" height=\"8\" fill=\"#e9c46a\"/>\
+                <text x=\"100\" y=\"195\" font-size=\"12\" text-anchor=\"middle\">#1</text></svg>"
+            );
+
+            let mut expected = String::from("data:image/svg+xml;base64,");
+            expected.extend_from_slice(&base64_encode(&contract.get_svg(token_id)));
+            assert_eq!(contract.get_image_uri(token_id), expected);
+        }
+
+        #[ink::test]
+        fn get_svg_follows_config_and_condition() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 7;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            // a starving pet in bad condition, with bars capped at 100
+            contract.set_status(token_id, 300, 0, 0).unwrap();
+
+            let config = SvgConfig {
+                backgrounds: vec![String::from("black")],
+                body: String::from("blue"),
+                hungry_bar: String::from("red"),
+                health_bar: String::from("green"),
+                happy_bar: String::from("yellow"),
+                layers: vec![String::from("<circle cx=\"100\" cy=\"40\" r=\"10\"/>")],
+            };
+            assert!(contract.set_svg_config(config.clone()).is_ok());
+            assert_eq!(contract.get_svg_config(), config);
+
+            contract.set_last_eaten(Id::U64(token_id), 1 * 1000);
+            set_block_timestamp(1 * 1000);
+            assert_eq!(
+                as_str(&contract.get_svg(token_id)),
+                "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 200 200\">\
+                <rect width=\"200\" height=\"200\" fill=\"black\"/>\
+                <circle cx=\"100\" cy=\"80\" r=\"40\" fill=\"blue\"/>\
+                <circle cx=\"86\" cy=\"72\" r=\"4\"/><circle cx=\"114\" cy=\"72\" r=\"4\"/>\
+                <path d=\"M86 100 Q100 88 114 100\" stroke=\"#000\" stroke-width=\"3\" fill=\"none\"/>\
+                <circle cx=\"100\" cy=\"40\" r=\"10\"/>\
+                <rect x=\"50\" y=\"140\" width=\"100\" height=\"8\" fill=\"red\"/>\
+                <rect x=\"50\" y=\"155\" width=\"0\" height=\"8\" fill=\"green\"/>\
+                <rect x=\"50\" y=\"170\" width=\"0\" height=\"8\" fill=\"yellow\"/>\
+                <text x=\"100\" y=\"195\" font-size=\"12\" text-anchor=\"middle\">#7</text></svg>"
+            );
+
+            set_caller(accounts.bob);
+            assert!(contract.set_svg_config(SvgConfig::default()).is_err());
+        }
+
+        #[ink::test]
+        fn new_sets_storage_version() {
+            let accounts = default_accounts();
+            let contract = Contract::new_with_owner(accounts.alice);
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+        }
+
+        #[ink::test]
+        fn migrate_works_in_batches() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            for token_id in 1..=3 {
+                assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            }
+            contract.storage_version.set(&(STORAGE_VERSION - 1));
+
+            assert_eq!(contract.migrate(0), Err(ContractError::InvalidMigrationBatch));
+            assert_eq!(contract.migrate(2), Ok(false));
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION - 1);
+            assert_eq!(contract.migration_cursor.get(), Some(2));
+
+            assert_eq!(contract.migrate(2), Ok(true));
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.migration_cursor.get(), Some(0));
+
+            // nothing left to do
+            assert_eq!(contract.migrate(2), Ok(true));
+        }
+
+        #[ink::test]
+        fn migrate_reaches_pets_minted_with_any_id() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            for token_id in [42, 7] {
+                assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+                contract.asset_status.insert(&Id::U64(token_id), &Status { hungry: 0, health: 100, happy: 100 });
+                contract.last_eaten.insert(&Id::U64(token_id), &(60 * 1000));
+            }
+            contract.storage_version.set(&2);
+
+            set_block_timestamp(60 * 1000);
+            assert_eq!(contract.migrate(100), Ok(true));
+            for token_id in [42, 7] {
+                assert_eq!(contract.asset_status.get(&Id::U64(token_id)), None);
+                assert_eq!(contract.get_status(token_id), Some(Status { hungry: 0, health: 100, happy: 100 }));
+                assert_eq!(contract.get_last_updated(token_id), Some(60 * 1000));
+            }
+        }
+
+        #[ink::test]
+        fn migrate_starts_the_critical_clock_at_migration() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_status(token_id, 0, 100, 100).unwrap();
+            contract.storage_version.set(&6);
+
+            // unfed for ten days, so starving long before lifecycles existed
+            let now = 10 * ONE_DAY;
+            set_block_timestamp(now);
+            assert_eq!(contract.migrate(10), Ok(true));
+            assert_eq!(contract.get_critical_since(token_id), Some(now));
+            assert_eq!(contract.get_pet_state(token_id), PetState::Critical);
+
+            let death_delay = contract.get_lifecycle_config().death_delay;
+            set_block_timestamp(now + death_delay - 1);
+            assert_eq!(contract.get_pet_state(token_id), PetState::Critical);
+            set_block_timestamp(now + death_delay);
+            assert_eq!(contract.get_pet_state(token_id), PetState::Dead);
+        }
+
+        #[ink::test]
+        fn migrate_moves_uris_into_tiers() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            contract.storage_version.set(&1);
+            contract.condition_tiers.set(&Vec::new());
+            contract.bad_uri = String::from("ipfs://bad/");
+            contract.normal_uri = String::from("ipfs://normal/");
+            contract.good_uri = String::from("ipfs://good/");
+
+            assert_eq!(contract.migrate(10), Ok(true));
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.get_bad_uri(), String::from("ipfs://bad/"));
+            assert_eq!(contract.get_normal_uri(), String::from("ipfs://normal/"));
+            assert_eq!(contract.get_good_uri(), String::from("ipfs://good/"));
+            assert_eq!(contract.get_condition_tiers().len(), 3);
+            assert_eq!(contract.bad_uri, String::new());
+        }
+
+        // the root cell of the first release; its mappings have cells of their own
+        #[ink::storage_item]
+        #[derive(Default)]
+        struct FirstRelease {
+            psp34: psp34::Data<enumerable::Balances>,
+            ownable: ownable::Data,
+            normal_uri: String,
+            good_uri: String,
+            bad_uri: String,
+            salt: u64,
+            current_token_id: u64,
+        }
+
+        #[ink::test]
+        fn migrate_upgrades_first_release_storage() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let root_key = <Contract as ink::storage::traits::StorageKey>::KEY;
+            let release = FirstRelease {
+                ownable: ownable::Data { owner: accounts.alice, ..Default::default() },
+                good_uri: String::from("ipfs://good/"),
+                salt: 7,
+                current_token_id: 1,
+                ..Default::default()
+            };
+            ink::env::set_contract_storage(&root_key, &release);
+
+            // what the new code loads after `set_code`
+            let mut contract: Contract = ink::env::get_contract_storage(&root_key)
+                .expect("the first release decodes")
+                .expect("the first release is stored");
+            assert_eq!(contract.get_storage_version(), 0);
+            assert_eq!(contract.owner(), accounts.alice);
+            assert_eq!(contract.salt, 7);
+
+            // a pet of the first release, fed a minute in
+            assert!(contract.mint(accounts.alice, Id::U64(1)).is_ok());
+            // metadata came after the first release and keeps out of its root cell
+            assert_eq!(contract.get_attribute(Id::U64(1), String::from("color")), None);
+            assert!(contract.set_token_attribute(1, String::from("color"), String::from("red")).is_ok());
+            assert_eq!(contract.get_attribute(Id::U64(1), String::from("color")), Some(String::from("red")));
+            assert_eq!(contract.salt, 7);
+            contract.asset_status.insert(&Id::U64(1), &Status { hungry: 0, health: 100, happy: 100 });
+            contract.last_eaten.insert(&Id::U64(1), &(60 * 1000));
+
+            set_block_timestamp(200 * 1000);
+            assert_eq!(contract.migrate(10), Ok(true));
+            assert_eq!(contract.get_storage_version(), STORAGE_VERSION);
+            assert_eq!(contract.get_good_uri(), String::from("ipfs://good/"));
+            assert_eq!(contract.get_condition_tiers().len(), 3);
+            assert_eq!(contract.asset_status.get(&Id::U64(1)), None);
+            assert_eq!(contract.get_last_updated(1), Some(60 * 1000));
+            assert_eq!(contract.get_current_status(1), Some(Status { hungry: 10, health: 90, happy: 90 }));
+            assert_eq!(contract.metadata_mode.get(), Some(MetadataMode::Ipfs));
+            assert_eq!(contract.svg_config.get(), Some(SvgConfig::default()));
+            assert_eq!(contract.decay_config.get(), Some(DecayConfig::default()));
+            assert_eq!(contract.stat_caps.get(), Some(StatCaps::default()));
+            assert_eq!(contract.lifecycle.get(), Some(LifecycleConfig::default()));
+            assert_eq!(contract.get_level_thresholds(), vec![100, 300, 600, 1000]);
+            assert_eq!(contract.xp_rewards.get(), Some(XpRewards::default()));
+            assert_eq!(contract.get_evolution_requirements(), Contract::default_evolution_requirements());
+            assert_eq!(contract.breed_config.get(), Some(BreedConfig::default()));
+            assert_eq!(contract.get_payment_token(), None);
+        }
+
+        #[ink::test]
+        fn condition_tiers_drive_condition_and_uri() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let token_id: u64 = 1;
+            assert!(contract.mint(accounts.alice, Id::U64(token_id)).is_ok());
+            contract.set_last_eaten(Id::U64(token_id), 1 * 1000);
+            set_block_timestamp(1 * 1000);
+            let caps = StatCaps { health: StatBounds { min: 0, max: 1000 }, ..Default::default() };
+            assert!(contract.set_stat_caps(caps).is_ok());
+
+            let tier = |min_score: u32, label: &str| ConditionTier {
+                min_score,
+                label: String::from(label),
+                base_uri: String::from(format!("ipfs://{}/", label).as_str()),
+            };
+            let tiers = vec![
+                tier(0, "critical"),
+                tier(20, "sick"),
+                tier(50, "bad"),
+                tier(100, "normal"),
+                tier(200, "good"),
+                tier(300, "excellent"),
+            ];
+            assert!(contract.set_condition_tiers(tiers.clone()).is_ok());
+            assert_eq!(contract.get_condition_tiers(), tiers);
+
+            let expected = [(0, 0, "critical"), (20, 1, "sick"), (99, 2, "bad"), (250, 4, "good"), (400, 5, "excellent")];
+            for (score, condition, label) in expected {
+                contract.set_status(token_id, 0, score, 0).unwrap();
+                assert_eq!(contract.get_condition(token_id), condition);
+                assert_eq!(contract.get_condition_label(token_id), String::from(label));
+                assert_eq!(
+                    contract.token_uri(token_id),
+                    String::from(format!("ipfs://{}/1", label).as_str())
+                );
+            }
+
+            // the legacy setters update the tier with the same label
+            assert!(contract.set_bad_uri(String::from("ipfs://new-bad/")).is_ok());
+            assert_eq!(contract.get_condition_tiers()[2].base_uri, String::from("ipfs://new-bad/"));
+        }
+
+        #[ink::test]
+        fn invalid_condition_tiers_are_rejected() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            let tier = |min_score: u32| ConditionTier { min_score, ..Default::default() };
+
+            assert_eq!(contract.set_condition_tiers(Vec::new()), Err(ContractError::InvalidConditionTiers));
+            assert_eq!(
+                contract.set_condition_tiers(vec![tier(10), tier(20)]),
+                Err(ContractError::InvalidConditionTiers)
+            );
+            assert_eq!(
+                contract.set_condition_tiers(vec![tier(0), tier(20), tier(20)]),
+                Err(ContractError::InvalidConditionTiers)
+            );
+            assert_eq!(
+                contract.set_condition_tiers((0..17).map(tier).collect()),
+                Err(ContractError::InvalidConditionTiers)
+            );
+            assert_eq!(contract.get_condition_tiers(), Contract::default_condition_tiers());
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_condition_tiers(vec![tier(0)]), Err(ContractError::OwnableError));
+        }
+
+        #[ink::test]
+        fn upgrade_needs_owner() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+            contract.storage_version.set(&0);
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_code([0x1; 32]), Err(ContractError::OwnableError));
+            assert_eq!(contract.migrate(10), Err(ContractError::OwnableError));
+            assert_eq!(contract.get_storage_version(), 0);
+        }
+
+        #[ink::test]
+        fn set_burn_rate_works() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = Contract::new_with_owner(accounts.alice);
+
+            assert!(contract.set_burn_rate(2500).is_ok());
+            assert_eq!(contract.get_burn_rate(), 2500);
+            assert_eq!(contract.get_burn_amount(500), 125);
+            assert_eq!(contract.get_burn_amount(Balance::MAX), Balance::MAX / 4);
+
+            assert_eq!(contract.set_burn_rate(10001), Err(ContractError::InvalidBurnRate));
+
+            set_caller(accounts.bob);
+            assert_eq!(contract.set_burn_rate(0), Err(ContractError::OwnableError));
+            assert_eq!(contract.get_burn_rate(), 2500);
+        }
  
     }
 
@@ -889,6 +3876,7 @@ pub mod my_psp34_mintable {
         use ink::primitives::AccountId;
         use ink_e2e::build_message;
         use openbrush::contracts::psp22::psp22_external::PSP22;
+        use openbrush::traits::String;
 
         type E2EResult<T> = Result<T, Box<dyn std::error::Error>>;
 
@@ -903,7 +3891,14 @@ pub mod my_psp34_mintable {
 
         #[ink_e2e::test(additional_contracts = "psp22/Cargo.toml psp34/Cargo.toml")]
         async fn it_works() -> E2EResult<()> {
-            let constructor = Psp22ContractRef::new(1000);
+            let constructor = Psp22ContractRef::new(
+                1000,
+                1000000,
+                get_bob_account_id(),
+                Some(String::from("Apple Coin")),
+                Some(String::from("APPLE")),
+                18
+            );
 
             // when
             let contract_acc_id = client
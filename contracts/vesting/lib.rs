@@ -0,0 +1,519 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+#![feature(min_specialization)]
+
+pub use self::token_vesting::{VestingContract, VestingContractRef};
+
+#[openbrush::contract]
+pub mod token_vesting {
+    use openbrush::{
+        contracts::ownable::*,
+        modifiers,
+        storage::Mapping,
+        traits::Storage,
+    };
+
+    use openbrush::contracts::psp22::psp22_external::PSP22;
+    use openbrush::contracts::psp22::PSP22Error;
+
+    use my_psp22_mintable::{ Psp22ContractRef};
+
+    use ink::prelude::vec::Vec;
+
+    // tokens locked for one beneficiary
+    #[derive(scale::Encode, scale::Decode, Debug, Clone, PartialEq, Default)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct VestingSchedule {
+        // tokens covered by the schedule
+        pub total: Balance,
+        // tokens already sent to the beneficiary
+        pub released: Balance,
+        // vesting start, in milliseconds
+        pub start: u64,
+        // nothing vests until start + cliff, in milliseconds
+        pub cliff: u64,
+        // everything has vested at start + duration, in milliseconds
+        pub duration: u64,
+        // whether the owner can take back the unvested part
+        pub revocable: bool,
+        pub revoked: bool,
+    }
+
+    impl VestingSchedule {
+        // tokens vested at `time`
+        pub fn vested_at(&self, time: u64) -> Balance {
+            if self.revoked {
+                // revoking pays out everything vested at that moment
+                self.released
+            } else if time < self.start.saturating_add(self.cliff) {
+                0
+            } else if time >= self.start.saturating_add(self.duration) {
+                self.total
+            } else {
+                let elapsed = time - self.start;
+                self.total.saturating_mul(elapsed as Balance) / self.duration as Balance
+            }
+        }
+
+        // tokens that can be released at `time`
+        pub fn releasable_at(&self, time: u64) -> Balance {
+            self.vested_at(time).saturating_sub(self.released)
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum VestingError {
+        PSP22Error,
+        OwnableError,
+        InvalidSchedule,
+        ScheduleAlreadyExists,
+        ScheduleNotFound,
+        NotEnoughTokens,
+        NothingToRelease,
+        NotRevocable,
+        AlreadyRevoked,
+    }
+
+    impl From<PSP22Error> for VestingError {
+        fn from(_: PSP22Error) -> Self {
+            Self::PSP22Error
+        }
+    }
+
+    impl From<OwnableError> for VestingError {
+        fn from(_: OwnableError) -> Self {
+            Self::OwnableError
+        }
+    }
+
+    #[derive(Default, Storage)]
+    #[ink(storage)]
+    pub struct VestingContract {
+        #[storage_field]
+        ownable: ownable::Data,
+
+        // the PSP22 token being vested
+        pub token: AccountId,
+
+        // vesting schedule of each beneficiary
+        pub schedules: Mapping<AccountId, VestingSchedule>,
+
+        // tokens held for schedules and not released yet
+        pub total_locked: Balance,
+    }
+
+    impl Ownable for VestingContract {}
+
+    impl VestingContract {
+        /// The constructor
+        ///
+        /// If the token charges a transfer fee, make this contract fee exempt; otherwise every
+        /// payout loses the fee and the last `release` fails for lack of tokens.
+        #[ink(constructor)]
+        pub fn new(token: AccountId) -> Self {
+            let mut instance = Self::default();
+            instance._init_with_owner(Self::env().caller());
+            instance.token = token;
+            instance
+        }
+
+        /// Locks `total` tokens for `beneficiary`.
+        ///
+        /// The tokens have to be transferred to this contract first. Nothing vests before
+        /// `start + cliff`, then tokens vest linearly until `start + duration`.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn create_schedule(
+            &mut self,
+            beneficiary: AccountId,
+            total: Balance,
+            start: u64,
+            cliff: u64,
+            duration: u64,
+            revocable: bool
+        ) -> Result<(), VestingError> {
+            if total == 0 || duration == 0 || cliff > duration {
+                return Err(VestingError::InvalidSchedule)
+            }
+            if self.schedules.get(&beneficiary).is_some() {
+                return Err(VestingError::ScheduleAlreadyExists)
+            }
+
+            let total_locked = self.total_locked.checked_add(total).ok_or(VestingError::NotEnoughTokens)?;
+            if self.get_token_balance() < total_locked {
+                return Err(VestingError::NotEnoughTokens)
+            }
+
+            self.schedules.insert(&beneficiary, &VestingSchedule {
+                total,
+                released: 0,
+                start,
+                cliff,
+                duration,
+                revocable,
+                revoked: false,
+            });
+            self.total_locked = total_locked;
+            Ok(())
+        }
+
+        /// Sends everything vested so far to `beneficiary`. Anyone can call it.
+        #[ink(message)]
+        pub fn release(&mut self, beneficiary: AccountId) -> Result<Balance, VestingError> {
+            let mut schedule = self.schedules.get(&beneficiary).ok_or(VestingError::ScheduleNotFound)?;
+
+            let amount = schedule.releasable_at(Self::env().block_timestamp());
+            if amount == 0 {
+                return Err(VestingError::NothingToRelease)
+            }
+
+            schedule.released += amount;
+            self.schedules.insert(&beneficiary, &schedule);
+            self.total_locked -= amount;
+            self.transfer_token(beneficiary, amount)?;
+            Ok(amount)
+        }
+
+        /// Stops a revocable schedule: the vested part goes to the beneficiary, the rest back to the owner.
+        #[ink(message)]
+        #[modifiers(only_owner)]
+        pub fn revoke(&mut self, beneficiary: AccountId) -> Result<(), VestingError> {
+            let mut schedule = self.schedules.get(&beneficiary).ok_or(VestingError::ScheduleNotFound)?;
+            if !schedule.revocable {
+                return Err(VestingError::NotRevocable)
+            }
+            if schedule.revoked {
+                return Err(VestingError::AlreadyRevoked)
+            }
+
+            let vested = schedule.releasable_at(Self::env().block_timestamp());
+            let unvested = schedule.total - schedule.released - vested;
+
+            schedule.released += vested;
+            schedule.revoked = true;
+            self.schedules.insert(&beneficiary, &schedule);
+            self.total_locked -= vested + unvested;
+
+            if vested > 0 {
+                self.transfer_token(beneficiary, vested)?;
+            }
+            if unvested > 0 {
+                let owner = self.owner();
+                self.transfer_token(owner, unvested)?;
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_schedule(&self, beneficiary: AccountId) -> Option<VestingSchedule> {
+            self.schedules.get(&beneficiary)
+        }
+
+        #[ink(message)]
+        pub fn get_vested_amount(&self, beneficiary: AccountId) -> Balance {
+            self.schedules
+                .get(&beneficiary)
+                .map(|schedule| schedule.vested_at(Self::env().block_timestamp()))
+                .unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_releasable_amount(&self, beneficiary: AccountId) -> Balance {
+            self.schedules
+                .get(&beneficiary)
+                .map(|schedule| schedule.releasable_at(Self::env().block_timestamp()))
+                .unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_token(&self) -> AccountId {
+            self.token
+        }
+
+        #[ink(message)]
+        pub fn get_total_locked(&self) -> Balance {
+            self.total_locked
+        }
+
+        // internal function
+
+        pub fn get_token_balance(&self) -> Balance {
+            let interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(self.token);
+            interface.balance_of(Self::env().account_id())
+        }
+
+        pub fn transfer_token(&mut self, to: AccountId, value: Balance) -> Result<(), PSP22Error> {
+            let mut interface: Psp22ContractRef = ink::env::call::FromAccountId::from_account_id(self.token);
+            interface.transfer(to, value, Vec::new())?;
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use ink::env::test;
+
+        fn default_accounts() -> test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            test::default_accounts::<ink::env::DefaultEnvironment>()
+        }
+
+        fn set_caller(sender: AccountId) {
+            ink::env::test::set_caller::<Environment>(sender);
+        }
+        fn set_block_timestamp(timestamp: u64) {
+            ink::env::test::set_block_timestamp::<Environment>(timestamp);
+        }
+
+        // 1000 tokens from 100 seconds, 10 second cliff, fully vested after 100 seconds
+        fn schedule(revocable: bool) -> VestingSchedule {
+            VestingSchedule {
+                total: 1000,
+                released: 0,
+                start: 100 * 1000,
+                cliff: 10 * 1000,
+                duration: 100 * 1000,
+                revocable,
+                revoked: false,
+            }
+        }
+
+        #[ink::test]
+        fn nothing_vests_before_cliff() {
+            let schedule = schedule(false);
+
+            assert_eq!(schedule.vested_at(0), 0);
+            assert_eq!(schedule.vested_at(100 * 1000), 0);
+            assert_eq!(schedule.vested_at(110 * 1000 - 1), 0);
+        }
+
+        #[ink::test]
+        fn vests_linearly_after_cliff() {
+            let schedule = schedule(false);
+
+            // the cliff releases what accrued during it
+            assert_eq!(schedule.vested_at(110 * 1000), 100);
+            assert_eq!(schedule.vested_at(150 * 1000), 500);
+            assert_eq!(schedule.vested_at(199 * 1000), 990);
+            assert_eq!(schedule.vested_at(200 * 1000), 1000);
+            assert_eq!(schedule.vested_at(1000 * 1000), 1000);
+        }
+
+        #[ink::test]
+        fn releasable_excludes_released() {
+            let mut schedule = schedule(false);
+            schedule.released = 300;
+
+            assert_eq!(schedule.releasable_at(150 * 1000), 200);
+            assert_eq!(schedule.releasable_at(120 * 1000), 0);
+        }
+
+        #[ink::test]
+        fn revoked_schedule_stops_vesting() {
+            let mut schedule = schedule(true);
+            schedule.released = 400;
+            schedule.revoked = true;
+
+            assert_eq!(schedule.vested_at(200 * 1000), 400);
+            assert_eq!(schedule.releasable_at(200 * 1000), 0);
+        }
+
+        #[ink::test]
+        fn queries_follow_block_timestamp() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = VestingContract::new(accounts.django);
+            contract.schedules.insert(&accounts.bob, &schedule(false));
+
+            set_block_timestamp(50 * 1000);
+            assert_eq!(contract.get_vested_amount(accounts.bob), 0);
+            assert_eq!(contract.release(accounts.bob), Err(VestingError::NothingToRelease));
+
+            set_block_timestamp(150 * 1000);
+            assert_eq!(contract.get_vested_amount(accounts.bob), 500);
+            assert_eq!(contract.get_releasable_amount(accounts.bob), 500);
+
+            assert_eq!(contract.get_vested_amount(accounts.charlie), 0);
+            assert_eq!(contract.release(accounts.charlie), Err(VestingError::ScheduleNotFound));
+        }
+
+        #[ink::test]
+        fn only_owner_can_create_and_revoke() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = VestingContract::new(accounts.django);
+            contract.schedules.insert(&accounts.bob, &schedule(true));
+
+            set_caller(accounts.bob);
+            assert_eq!(
+                contract.create_schedule(accounts.bob, 1000, 0, 0, 1000, false),
+                Err(VestingError::OwnableError)
+            );
+            assert_eq!(contract.revoke(accounts.bob), Err(VestingError::OwnableError));
+        }
+
+        #[ink::test]
+        fn invalid_schedules_are_rejected() {
+            let accounts = default_accounts();
+            set_caller(accounts.alice);
+            let mut contract = VestingContract::new(accounts.django);
+            contract.schedules.insert(&accounts.bob, &schedule(false));
+
+            assert_eq!(
+                contract.create_schedule(accounts.charlie, 0, 0, 0, 1000, false),
+                Err(VestingError::InvalidSchedule)
+            );
+            assert_eq!(
+                contract.create_schedule(accounts.charlie, 1000, 0, 0, 0, false),
+                Err(VestingError::InvalidSchedule)
+            );
+            assert_eq!(
+                contract.create_schedule(accounts.charlie, 1000, 0, 2000, 1000, false),
+                Err(VestingError::InvalidSchedule)
+            );
+            assert_eq!(
+                contract.create_schedule(accounts.bob, 1000, 0, 0, 1000, false),
+                Err(VestingError::ScheduleAlreadyExists)
+            );
+            assert_eq!(contract.revoke(accounts.bob), Err(VestingError::NotRevocable));
+        }
+    }
+
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        use super::*;
+        use my_psp22_mintable::my_psp22_mintable::Psp22ContractRef;
+        use ink_e2e::build_message;
+        use openbrush::traits::String;
+
+        type E2EResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+        // the client `ink_e2e::test` hands to each test
+        type Client = ink_e2e::Client<ink_e2e::PolkadotConfig, ink::env::DefaultEnvironment>;
+
+        fn bob() -> AccountId {
+            ink_e2e::account_id(ink_e2e::AccountKeyring::Bob)
+        }
+
+        fn charlie() -> AccountId {
+            ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie)
+        }
+
+        fn dave() -> AccountId {
+            ink_e2e::account_id(ink_e2e::AccountKeyring::Dave)
+        }
+
+        // Bob deploys the token with a 1% transfer fee to Charlie, then the vesting contract,
+        // exempts it from the fee and funds it with 1000 tokens
+        async fn deploy(client: &mut Client) -> E2EResult<(AccountId, AccountId)> {
+            let constructor = Psp22ContractRef::new(
+                10000,
+                1000000,
+                bob(),
+                Some(String::from("Apple Coin")),
+                Some(String::from("APPLE")),
+                18
+            );
+            let token = client
+                .instantiate("my_psp22_mintable", &ink_e2e::bob(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let constructor = VestingContractRef::new(token);
+            let vesting = client
+                .instantiate("token_vesting", &ink_e2e::bob(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let set_fee = build_message::<Psp22ContractRef>(token.clone())
+                .call(|psp22| psp22.set_transfer_fee(100, charlie()));
+            client.call(&ink_e2e::bob(), set_fee, 0, None).await.expect("set_transfer_fee failed");
+            let exempt = build_message::<Psp22ContractRef>(token.clone())
+                .call(|psp22| psp22.set_fee_exempt(vesting, true));
+            client.call(&ink_e2e::bob(), exempt, 0, None).await.expect("set_fee_exempt failed");
+
+            let fund = build_message::<Psp22ContractRef>(token.clone())
+                .call(|psp22| psp22.transfer(vesting, 1000, Vec::new()));
+            client.call(&ink_e2e::bob(), fund, 0, None).await.expect("transfer failed");
+
+            Ok((token, vesting))
+        }
+
+        async fn balance_of(client: &mut Client, token: AccountId, account: AccountId) -> Balance {
+            let balance_of = build_message::<Psp22ContractRef>(token)
+                .call(|psp22| psp22.balance_of(account));
+            client
+                .call_dry_run(&ink_e2e::bob(), &balance_of, 0, None)
+                .await
+                .return_value()
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn create_and_release_pay_the_beneficiary(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let (token, vesting) = deploy(&mut client).await?;
+
+            // fully vested from the start
+            let create = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.create_schedule(dave(), 1000, 0, 0, 1, false));
+            client.call(&ink_e2e::bob(), create, 0, None).await.expect("create_schedule failed");
+
+            let too_much = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.create_schedule(charlie(), 1, 0, 0, 1, false));
+            let too_much = client.call_dry_run(&ink_e2e::bob(), &too_much, 0, None).await;
+            assert_eq!(too_much.return_value(), Err(VestingError::NotEnoughTokens));
+
+            // anyone can release
+            let release = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.release(dave()));
+            client.call(&ink_e2e::charlie(), release, 0, None).await.expect("release failed");
+
+            assert_eq!(balance_of(&mut client, token, dave()).await, 1000);
+            assert_eq!(balance_of(&mut client, token, vesting).await, 0);
+
+            let get_total_locked = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.get_total_locked());
+            let total_locked = client.call_dry_run(&ink_e2e::bob(), &get_total_locked, 0, None).await;
+            assert_eq!(total_locked.return_value(), 0);
+
+            let release = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.release(dave()));
+            let release = client.call_dry_run(&ink_e2e::charlie(), &release, 0, None).await;
+            assert_eq!(release.return_value(), Err(VestingError::NothingToRelease));
+
+            Ok(())
+        }
+
+        #[ink_e2e::test(additional_contracts = "../psp22/Cargo.toml")]
+        async fn revoke_returns_unvested_tokens(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            let (token, vesting) = deploy(&mut client).await?;
+            let bob_balance = balance_of(&mut client, token, bob()).await;
+
+            // starts so far ahead that nothing has vested when it is revoked
+            let create = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.create_schedule(dave(), 1000, u64::MAX / 2, 0, 1000, true));
+            client.call(&ink_e2e::bob(), create, 0, None).await.expect("create_schedule failed");
+
+            let revoke = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.revoke(dave()));
+            let not_owner = client.call_dry_run(&ink_e2e::dave(), &revoke, 0, None).await;
+            assert_eq!(not_owner.return_value(), Err(VestingError::OwnableError));
+            client.call(&ink_e2e::bob(), revoke, 0, None).await.expect("revoke failed");
+
+            assert_eq!(balance_of(&mut client, token, bob()).await, bob_balance + 1000);
+            assert_eq!(balance_of(&mut client, token, dave()).await, 0);
+            assert_eq!(balance_of(&mut client, token, vesting).await, 0);
+
+            let get_schedule = build_message::<VestingContractRef>(vesting.clone())
+                .call(|contract| contract.get_schedule(dave()));
+            let schedule = client.call_dry_run(&ink_e2e::bob(), &get_schedule, 0, None).await;
+            assert!(schedule.return_value().expect("schedule is kept").revoked);
+
+            Ok(())
+        }
+    }
+}
@@ -32,7 +32,16 @@ describe("psp22 test", () => {
     psp22Factory = new Psp22Factory(api, deployer);
 
     contract = new Psp22(
-      (await psp22Factory.new(maxSupply)).address,
+      (
+        await psp22Factory.new(
+          maxSupply,
+          maxSupply * 2,
+          deployer.address,
+          "Apple Coin",
+          "APPLE",
+          18
+        )
+      ).address,
       deployer,
       api
     );
@@ -82,6 +91,28 @@ describe("psp22 test", () => {
     ).to.eventually.be.rejected;
   });
 
+  it("Has token metadata", async () => {
+    expect((await contract.query.tokenName()).value).to.equal("Apple Coin");
+    expect((await contract.query.tokenSymbol()).value).to.equal("APPLE");
+    expect((await contract.query.tokenDecimals()).value).to.equal(18);
+  });
+
+  it("Can not mint without minter role", async () => {
+    const { gasRequired } = await contract
+      .withSigner(deployer)
+      .query.mintTo(wallet1.address, 10);
+
+    await expect(
+      contract.withSigner(wallet1).tx.mintTo(wallet1.address, 10, {
+        gasLimit: gasRequired,
+      })
+    ).to.eventually.be.rejected;
+
+    expect(
+      (await contract.query.totalSupply()).value.rawNumber.toNumber()
+    ).to.equal(maxSupply);
+  });
+
   it("Can not transfer to hated account", async () => {
     const hated_account = wallet2;
     const transferredAmount = 10;